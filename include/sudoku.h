@@ -0,0 +1,80 @@
+/*
+ * C interface of the sudoku-solver library.
+ *
+ * Every function returns SUDOKU_OK or an error code. If the error argument
+ * is not NULL, a failing function also stores the code and a message there;
+ * free the message with sudoku_error_free. Puzzles from sudoku_parse and
+ * sudoku_generate are owned by the caller and freed with sudoku_free, and
+ * strings from sudoku_to_string are freed with sudoku_string_free.
+ */
+
+#ifndef SUDOKU_H
+#define SUDOKU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SUDOKU_OK 0
+#define SUDOKU_ERROR_NULL_POINTER 1
+#define SUDOKU_ERROR_INVALID_ARGUMENT 2
+#define SUDOKU_ERROR_PARSE 3
+#define SUDOKU_ERROR_NO_SOLUTION 4
+#define SUDOKU_ERROR_PANIC 5
+
+typedef struct SudokuPuzzle SudokuPuzzle;
+
+typedef struct SudokuError {
+    int32_t code;
+    char *message;
+} SudokuError;
+
+/* row and column start from 0. technique is 0 for a naked single, 1 for a
+ * hidden single and 2 when the digit comes from the solution. */
+typedef struct SudokuHint {
+    uint32_t row;
+    uint32_t column;
+    uint8_t value;
+    uint32_t technique;
+} SudokuHint;
+
+/* Parses a puzzle in the puzzle file format. */
+int32_t sudoku_parse(const char *text, SudokuPuzzle **out, SudokuError *error);
+
+/* Generates a puzzle of level 1 (easy) to 3 (hard). Only in a library built
+ * with the generator feature. */
+int32_t sudoku_generate(uint32_t level, SudokuPuzzle **out, SudokuError *error);
+
+/* Frees a puzzle. NULL is ignored. */
+void sudoku_free(SudokuPuzzle *puzzle);
+
+/* Solves the puzzle in place. */
+int32_t sudoku_solve(SudokuPuzzle *puzzle, SudokuError *error);
+
+/* Counts the solutions up to limit into count. */
+int32_t sudoku_count_solutions(const SudokuPuzzle *puzzle, size_t limit, size_t *count,
+                               SudokuError *error);
+
+/* Finds the next digit to place. */
+int32_t sudoku_hint(const SudokuPuzzle *puzzle, SudokuHint *out, SudokuError *error);
+
+/* Copies the 81 digits in row-major order to cells, 0 for an empty cell. */
+int32_t sudoku_get_cells(const SudokuPuzzle *puzzle, uint8_t cells[81], SudokuError *error);
+
+/* Writes the puzzle in the puzzle file format to a new string. */
+int32_t sudoku_to_string(const SudokuPuzzle *puzzle, char **out, SudokuError *error);
+
+/* Frees a string from sudoku_to_string. NULL is ignored. */
+void sudoku_string_free(char *string);
+
+/* Frees the message of an error and resets it. NULL is ignored. */
+void sudoku_error_free(SudokuError *error);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SUDOKU_H */
@@ -0,0 +1,156 @@
+//! Serves the operations of `sudoku::api` as a JSON HTTP API on localhost.
+//!
+//! `POST /solve`, `/validate`, `/rate` and `/hint` take a grid as
+//! `{"squares": [...]}` or `{"puzzle": "<puzzle file>"}`. `GET /generate`
+//! and `GET /new` take `size` and `level` in the query like the cs.utep.edu
+//! puzzle service, so that `SUDOKU_SERVICE_URL=http://localhost:8080/new`
+//! makes the CLI fetch its puzzles from here.
+
+use serde_json::{json, Map, Value};
+use sudoku_solver::sudoku::api::{self, Error};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Command line arguments: `[--port 8080]`
+fn parse_args() -> Result<u16, String> {
+    let mut port = 8080;
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--port" => {
+                port = iter
+                    .next()
+                    .and_then(|port| port.parse().ok())
+                    .ok_or("--port needs a port number")?
+            }
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    Ok(port)
+}
+
+fn main() {
+    let port = parse_args().expect("Invalid arguments");
+    let server = Server::http(("127.0.0.1", port)).expect("Failed to start the server");
+    println!("Listening on http://127.0.0.1:{}", port);
+
+    for mut request in server.incoming_requests() {
+        let body = read_body(&mut request);
+        let (status, body) = handle(request.method(), request.url(), body);
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        if let Err(error) = request.respond(response) {
+            eprintln!("Failed to respond: {}", error);
+        }
+    }
+}
+
+/// Returns the status code and the JSON body of the response to a request
+/// with the JSON `body`. Errors have `"response": false` like the failures
+/// of the puzzle service.
+fn handle(method: &Method, url: &str, body: Result<Value, String>) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let api_method = route(path).unwrap_or("");
+
+    let params = match method {
+        Method::Get => Ok(query_params(query)),
+        Method::Post => body,
+        _ => return (405, error("only GET and POST are supported")),
+    };
+    let result = params
+        .map_err(Error::InvalidParams)
+        .and_then(|params| api::call(api_method, &params));
+    match result {
+        Ok(value) => (200, value),
+        Err(e @ Error::UnknownMethod(_)) => (404, error(&e.to_string())),
+        Err(e @ Error::InvalidParams(_)) => (400, error(&e.to_string())),
+        Err(e @ Error::Failed(_)) => (422, error(&e.to_string())),
+    }
+}
+
+/// The `api` method of a path with one segment like `/solve`, or `None` for
+/// a path that is not served. `/new` is `generate` like in the puzzle service.
+fn route(path: &str) -> Option<&str> {
+    match path.trim_end_matches('/').strip_prefix('/')? {
+        "new" => Some("generate"),
+        method if !method.is_empty() && !method.contains('/') => Some(method),
+        _ => None,
+    }
+}
+
+fn read_body(request: &mut Request) -> Result<Value, String> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| e.to_string())?;
+    if body.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+/// The query as an object of strings. The values are not percent-decoded,
+/// which is enough for the numbers of `generate`.
+fn query_params(query: &str) -> Value {
+    let params: Map<String, Value> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+        .collect();
+    Value::Object(params)
+}
+
+fn error(message: &str) -> Value {
+    json!({ "response": false, "error": message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....\\n6..195...\\n.98....6.\\n8...6...3\\n4..8.3..1\\n\
+                          7...2...6\\n.6....28.\\n...419..5\\n....8..79\\n";
+
+    #[test]
+    fn test_route() {
+        assert_eq!(Some("solve"), route("/solve"));
+        assert_eq!(Some("solve"), route("/solve/"));
+        assert_eq!(Some("generate"), route("/new"));
+        assert_eq!(None, route("/foo/bar/solve"));
+        assert_eq!(None, route("/"));
+        assert_eq!(None, route("solve"));
+    }
+
+    #[test]
+    fn test_handle() {
+        let body = serde_json::from_str(&format!(r#"{{"puzzle": "{}"}}"#, PUZZLE));
+        let (status, value) = handle(&Method::Post, "/solve", body.map_err(|e| e.to_string()));
+        assert_eq!(200, status);
+        assert_eq!(Some(true), value["response"].as_bool());
+
+        let (status, value) = handle(&Method::Post, "/foo/bar/solve", Ok(json!({})));
+        assert_eq!(404, status);
+        assert_eq!(Some(false), value["response"].as_bool());
+
+        let (status, _) = handle(&Method::Post, "/solve", Err(String::from("bad JSON")));
+        assert_eq!(400, status);
+
+        let (status, _) = handle(&Method::Delete, "/solve", Ok(json!({})));
+        assert_eq!(405, status);
+
+        let (status, value) = handle(&Method::Get, "/new?level=1", Ok(json!({})));
+        assert_eq!(200, status);
+        assert_eq!(Some("9"), value["size"].as_str());
+    }
+
+    #[test]
+    fn test_query_params() {
+        assert_eq!(
+            json!({ "size": "9", "level": "2" }),
+            query_params("size=9&level=2&flag")
+        );
+        assert_eq!(json!({}), query_params(""));
+    }
+}
@@ -0,0 +1,380 @@
+//! C interface of the library, declared in `include/sudoku.h`.
+//!
+//! Every function returns `SUDOKU_OK` or an error code. If the `error`
+//! argument is not null, a failing function also stores the code and a
+//! message there, which the caller frees with `sudoku_error_free`. Puzzles
+//! are owned by the caller from `sudoku_parse` or `sudoku_generate` until
+//! `sudoku_free`, and strings from `sudoku_to_string` until
+//! `sudoku_string_free`. `sudoku_generate` needs the `generator` feature.
+
+#[cfg(feature = "generator")]
+use crate::sudoku::generator;
+use crate::sudoku::hint::{self, Technique};
+#[cfg(feature = "generator")]
+use crate::sudoku::rating::Difficulty;
+use crate::sudoku::variant::Puzzle;
+use crate::sudoku::{format, solver, Position};
+#[cfg(feature = "generator")]
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+pub const SUDOKU_OK: i32 = 0;
+pub const SUDOKU_ERROR_NULL_POINTER: i32 = 1;
+pub const SUDOKU_ERROR_INVALID_ARGUMENT: i32 = 2;
+pub const SUDOKU_ERROR_PARSE: i32 = 3;
+pub const SUDOKU_ERROR_NO_SOLUTION: i32 = 4;
+pub const SUDOKU_ERROR_PANIC: i32 = 5;
+
+/// An error code and a message allocated by the library.
+#[repr(C)]
+pub struct SudokuError {
+    pub code: i32,
+    pub message: *mut c_char,
+}
+
+/// A digit to place: `row` and `column` start from 0, `technique` is 0 for a
+/// naked single, 1 for a hidden single and 2 when the digit comes from the
+/// solution.
+#[repr(C)]
+pub struct SudokuHint {
+    pub row: u32,
+    pub column: u32,
+    pub value: u8,
+    pub technique: u32,
+}
+
+/// A puzzle, opaque to C.
+pub struct SudokuPuzzle(Puzzle);
+
+type Failure = (i32, String);
+
+/// Runs `f`, stores its failure to `error` and turns panics into
+/// `SUDOKU_ERROR_PANIC` so that they do not unwind into C.
+unsafe fn guard(error: *mut SudokuError, f: impl FnOnce() -> Result<(), Failure>) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err((SUDOKU_ERROR_PANIC, "panic in the library".to_string())));
+    match result {
+        Ok(()) => SUDOKU_OK,
+        Err((code, message)) => {
+            if !error.is_null() {
+                let message = CString::new(message.replace('\0', " ")).unwrap();
+                *error = SudokuError {
+                    code,
+                    message: message.into_raw(),
+                };
+            }
+            code
+        }
+    }
+}
+
+unsafe fn puzzle_ref<'a>(puzzle: *const SudokuPuzzle) -> Result<&'a Puzzle, Failure> {
+    puzzle
+        .as_ref()
+        .map(|puzzle| &puzzle.0)
+        .ok_or_else(null_pointer)
+}
+
+fn null_pointer() -> Failure {
+    (SUDOKU_ERROR_NULL_POINTER, "null pointer".to_string())
+}
+
+/// Parses a puzzle in the puzzle file format.
+///
+/// # Safety
+///
+/// `text` must be a null-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_parse(
+    text: *const c_char,
+    out: *mut *mut SudokuPuzzle,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        if text.is_null() || out.is_null() {
+            return Err(null_pointer());
+        }
+        let text = CStr::from_ptr(text)
+            .to_str()
+            .map_err(|e| (SUDOKU_ERROR_PARSE, e.to_string()))?;
+        let puzzle: Puzzle = text.parse().map_err(|e| (SUDOKU_ERROR_PARSE, e))?;
+        *out = Box::into_raw(Box::new(SudokuPuzzle(puzzle)));
+        Ok(())
+    })
+}
+
+/// Generates a puzzle of level 1 (easy) to 3 (hard).
+///
+/// # Safety
+///
+/// `out` must be a valid pointer.
+#[cfg(feature = "generator")]
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_generate(
+    level: u32,
+    out: *mut *mut SudokuPuzzle,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        if out.is_null() {
+            return Err(null_pointer());
+        }
+        let difficulty = u8::try_from(level)
+            .ok()
+            .and_then(Difficulty::from_level)
+            .ok_or_else(|| {
+                (
+                    SUDOKU_ERROR_INVALID_ARGUMENT,
+                    "level must be 1, 2 or 3".to_string(),
+                )
+            })?;
+        let puzzle = Puzzle {
+            sudoku: generator::generate(difficulty),
+            constraints: Vec::new(),
+        };
+        *out = Box::into_raw(Box::new(SudokuPuzzle(puzzle)));
+        Ok(())
+    })
+}
+
+/// Frees a puzzle. Null is ignored.
+///
+/// # Safety
+///
+/// `puzzle` must come from `sudoku_parse` or `sudoku_generate` and not be
+/// used after this.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_free(puzzle: *mut SudokuPuzzle) {
+    if !puzzle.is_null() {
+        drop(Box::from_raw(puzzle));
+    }
+}
+
+/// Solves the puzzle in place.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(puzzle: *mut SudokuPuzzle, error: *mut SudokuError) -> i32 {
+    guard(error, || {
+        let puzzle = &mut puzzle.as_mut().ok_or_else(null_pointer)?.0;
+        solver::try_solve(puzzle).map_err(|contradiction| {
+            (
+                SUDOKU_ERROR_NO_SOLUTION,
+                format!("no solution: {}", contradiction),
+            )
+        })
+    })
+}
+
+/// Counts the solutions up to `limit` into `count`.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `count` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_count_solutions(
+    puzzle: *const SudokuPuzzle,
+    limit: usize,
+    count: *mut usize,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        let puzzle = puzzle_ref(puzzle)?;
+        if count.is_null() {
+            return Err(null_pointer());
+        }
+        *count = solver::count_solutions(puzzle, limit);
+        Ok(())
+    })
+}
+
+/// Finds the next digit to place.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_hint(
+    puzzle: *const SudokuPuzzle,
+    out: *mut SudokuHint,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        let puzzle = puzzle_ref(puzzle)?;
+        if out.is_null() {
+            return Err(null_pointer());
+        }
+        let hint = hint::hint(puzzle).ok_or_else(|| {
+            (
+                SUDOKU_ERROR_NO_SOLUTION,
+                "no hint, the grid is full or has no solution".to_string(),
+            )
+        })?;
+        *out = SudokuHint {
+            row: hint.position.y as u32,
+            column: hint.position.x as u32,
+            value: hint.value,
+            technique: match hint.technique {
+                Technique::NakedSingle => 0,
+                Technique::HiddenSingle => 1,
+                Technique::Solution => 2,
+            },
+        };
+        Ok(())
+    })
+}
+
+/// Copies the 81 digits in row-major order to `cells`, 0 for an empty cell.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `cells` must have room for 81 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_get_cells(
+    puzzle: *const SudokuPuzzle,
+    cells: *mut u8,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        let puzzle = puzzle_ref(puzzle)?;
+        if cells.is_null() {
+            return Err(null_pointer());
+        }
+        for i in 0..81 {
+            let position = Position { x: i % 9, y: i / 9 };
+            *cells.add(i) = puzzle.sudoku.value(position).unwrap_or(0);
+        }
+        Ok(())
+    })
+}
+
+/// Writes the puzzle in the puzzle file format to a new string.
+///
+/// # Safety
+///
+/// `puzzle` must be a valid puzzle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_to_string(
+    puzzle: *const SudokuPuzzle,
+    out: *mut *mut c_char,
+    error: *mut SudokuError,
+) -> i32 {
+    guard(error, || {
+        let puzzle = puzzle_ref(puzzle)?;
+        if out.is_null() {
+            return Err(null_pointer());
+        }
+        *out = CString::new(format::write(puzzle)).unwrap().into_raw();
+        Ok(())
+    })
+}
+
+/// Frees a string from `sudoku_to_string`. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must come from `sudoku_to_string` and not be used after this.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Frees the message of an error and resets it. Null is ignored.
+///
+/// # Safety
+///
+/// `error` must be null or filled by a function of this library.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_error_free(error: *mut SudokuError) {
+    if let Some(error) = error.as_mut() {
+        sudoku_string_free(error.message);
+        error.code = SUDOKU_OK;
+        error.message = ptr::null_mut();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_solve_and_free() {
+        let text = CString::new(format!("12345678.{}", ".".repeat(72))).unwrap();
+        let mut error = SudokuError {
+            code: SUDOKU_OK,
+            message: ptr::null_mut(),
+        };
+        unsafe {
+            let mut puzzle = ptr::null_mut();
+            assert_eq!(
+                sudoku_parse(text.as_ptr(), &mut puzzle, &mut error),
+                SUDOKU_OK
+            );
+
+            let mut hint = SudokuHint {
+                row: 0,
+                column: 0,
+                value: 0,
+                technique: 0,
+            };
+            assert_eq!(sudoku_hint(puzzle, &mut hint, &mut error), SUDOKU_OK);
+            assert_eq!((hint.row, hint.column, hint.value), (0, 8, 9));
+
+            let mut count = 0;
+            sudoku_count_solutions(puzzle, 2, &mut count, &mut error);
+            assert_eq!(count, 2);
+
+            assert_eq!(sudoku_solve(puzzle, &mut error), SUDOKU_OK);
+            let mut cells = [0u8; 81];
+            assert_eq!(
+                sudoku_get_cells(puzzle, cells.as_mut_ptr(), &mut error),
+                SUDOKU_OK
+            );
+            assert!(cells.iter().all(|value| (1..=9).contains(value)));
+
+            let mut string = ptr::null_mut();
+            assert_eq!(sudoku_to_string(puzzle, &mut string, &mut error), SUDOKU_OK);
+            assert!(CStr::from_ptr(string)
+                .to_str()
+                .unwrap()
+                .starts_with("123456789"));
+            sudoku_string_free(string);
+            sudoku_free(puzzle);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let text = CString::new("12x").unwrap();
+        let mut error = SudokuError {
+            code: SUDOKU_OK,
+            message: ptr::null_mut(),
+        };
+        unsafe {
+            let mut puzzle = ptr::null_mut();
+            let code = sudoku_parse(text.as_ptr(), &mut puzzle, &mut error);
+            assert_eq!(code, SUDOKU_ERROR_PARSE);
+            assert_eq!(error.code, SUDOKU_ERROR_PARSE);
+            assert!(!CStr::from_ptr(error.message).to_bytes().is_empty());
+            sudoku_error_free(&mut error);
+            assert!(error.message.is_null());
+
+            #[cfg(feature = "generator")]
+            {
+                let code = sudoku_generate(7, &mut puzzle, ptr::null_mut());
+                assert_eq!(code, SUDOKU_ERROR_INVALID_ARGUMENT);
+            }
+            assert_eq!(
+                sudoku_solve(ptr::null_mut(), ptr::null_mut()),
+                SUDOKU_ERROR_NULL_POINTER
+            );
+        }
+    }
+}
@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate lazy_static; // used in sudoku::solver
+
+#[cfg(test)]
+#[macro_use]
+extern crate time_test; // used in sudoku::solver::test
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ffi;
+pub mod sudoku;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
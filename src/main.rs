@@ -1,18 +1,176 @@
-#[macro_use]
-extern crate lazy_static; // used in sudoku::solver
+mod tui;
 
-#[cfg(test)]
-#[macro_use]
-extern crate time_test; // used in sudoku::solver::test
+use sudoku::render;
+use sudoku::render::terminal::{self, Style};
+use sudoku::variant::Puzzle;
+use sudoku_solver::sudoku::{
+    self, explain,
+    get::{self, Difficulty},
+};
 
-pub mod sudoku;
+/// Command line arguments:
+/// `[puzzle file] [--difficulty easy|medium|hard] [--svg output.svg] [--png output.png]
+/// [--scale pixels per unit] [--pencil-marks] [--solution]`
+/// or to play in the terminal
+/// `[puzzle file] --play [--difficulty easy|medium|hard] [--save game.json]`
+/// or `--load game.json`
+/// or for JSON-RPC requests on stdin `--rpc`
+/// or for a booklet of puzzles from the service
+/// `--pdf output.pdf [--count n] [--per-page 1|2|4|6] [--difficulty easy|medium|hard]`
+struct Args {
+    puzzle_file: Option<String>,
+    difficulty: Difficulty,
+    svg_file: Option<String>,
+    png_file: Option<String>,
+    scale: f32,
+    pencil_marks: bool,
+    /// Draw the solved puzzle to the SVG and PNG instead of the puzzle.
+    solution: bool,
+    pdf_file: Option<String>,
+    count: usize,
+    per_page: render::pdf::PageLayout,
+    play: bool,
+    save_file: String,
+    load_file: Option<String>,
+    rpc: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        puzzle_file: None,
+        difficulty: Difficulty::Hard,
+        svg_file: None,
+        png_file: None,
+        scale: 1.0,
+        pencil_marks: false,
+        solution: false,
+        pdf_file: None,
+        count: 4,
+        per_page: render::pdf::PageLayout::Four,
+        play: false,
+        save_file: "sudoku-game.json".to_string(),
+        load_file: None,
+        rpc: false,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--svg" => args.svg_file = Some(value()?),
+            "--png" => args.png_file = Some(value()?),
+            "--scale" => args.scale = value()?.parse().map_err(|_| "invalid --scale")?,
+            "--pencil-marks" => args.pencil_marks = true,
+            "--solution" => args.solution = true,
+            "--difficulty" => args.difficulty = value()?.parse()?,
+            "--pdf" => args.pdf_file = Some(value()?),
+            "--count" => args.count = value()?.parse().map_err(|_| "invalid --count")?,
+            "--per-page" => {
+                args.per_page = value()?
+                    .parse()
+                    .ok()
+                    .and_then(render::pdf::PageLayout::from_count)
+                    .ok_or("--per-page must be 1, 2, 4 or 6")?
+            }
+            "--play" => args.play = true,
+            "--rpc" => args.rpc = true,
+            "--save" => args.save_file = value()?,
+            "--load" => args.load_file = Some(value()?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => args.puzzle_file = Some(arg),
+        }
+    }
+    Ok(args)
+}
 
 fn main() {
+    let args = parse_args().expect("Invalid arguments");
+    if args.rpc {
+        let stdin = std::io::stdin();
+        sudoku::rpc::serve(stdin.lock(), std::io::stdout()).expect("Failed to serve");
+        return;
+    }
     println!("Sudoku solver");
 
-    let mut sudoku =
-        sudoku::get::create_sudoku(sudoku::get::Difficulty::Hard).expect("Failed to get sudoku");
-    println!("Sudoku = {}", sudoku);
-    sudoku::solver::solve(&mut sudoku);
-    println!("solved Sudoku = {}", sudoku);
+    if let Some(pdf_file) = &args.pdf_file {
+        write_booklet(pdf_file, &args);
+        return;
+    }
+    if let Some(load_file) = &args.load_file {
+        tui::resume(load_file).expect("Failed to play");
+        return;
+    }
+
+    let mut puzzle = match &args.puzzle_file {
+        Some(path) => read_puzzle(path),
+        None => Puzzle {
+            sudoku: get::create_sudoku(args.difficulty).expect("Failed to get sudoku"),
+            constraints: Vec::new(),
+        },
+    };
+    if args.play {
+        tui::play(puzzle, &args.save_file).expect("Failed to play");
+        return;
+    }
+    let options = render::Options {
+        title: args.puzzle_file.clone(),
+        givens: Some(puzzle.sudoku),
+        pencil_marks: args.pencil_marks,
+        ..render::Options::default()
+    };
+    let style = Style::detect();
+
+    println!(
+        "Sudoku =\n{}",
+        terminal::write_puzzle(&puzzle, &options, style)
+    );
+    let mut drawing = render::draw_puzzle(&puzzle, &options);
+    match sudoku::solver::try_solve(&mut puzzle) {
+        Ok(()) => {
+            println!(
+                "solved Sudoku =\n{}",
+                terminal::write_puzzle(&puzzle, &options, style)
+            );
+            if args.solution {
+                drawing = render::draw_puzzle(&puzzle, &options);
+            }
+        }
+        Err(contradiction) => {
+            println!("Sudoku has no solution: {}", contradiction);
+            if let Some(givens) = explain::unsolvable_givens(&puzzle) {
+                let givens: Vec<String> =
+                    givens.iter().map(|position| position.to_string()).collect();
+                println!("These givens cannot be together: {}", givens.join(" "));
+            }
+        }
+    }
+
+    if let Some(svg_file) = &args.svg_file {
+        let svg = render::svg::write(&drawing);
+        std::fs::write(svg_file, svg).expect("Failed to write SVG");
+    }
+    if let Some(png_file) = &args.png_file {
+        let png = render::png::write(&drawing, args.scale).expect("Failed to render PNG");
+        std::fs::write(png_file, png).expect("Failed to write PNG");
+    }
+}
+
+fn read_puzzle(path: &str) -> Puzzle {
+    let content = std::fs::read_to_string(path).expect("Failed to read puzzle file");
+    content.parse().expect("Failed to parse puzzle")
+}
+
+fn write_booklet(pdf_file: &str, args: &Args) {
+    let puzzles: Vec<_> = (0..args.count)
+        .map(|_| {
+            let sudoku = get::create_sudoku(args.difficulty).expect("Failed to get sudoku");
+            let puzzle = Puzzle {
+                sudoku,
+                constraints: Vec::new(),
+            };
+            (puzzle, None)
+        })
+        .collect();
+    let pdf = render::pdf::booklet(&puzzles, args.per_page);
+    std::fs::write(pdf_file, pdf).expect("Failed to write PDF");
+    println!("Wrote {} puzzles to {}", puzzles.len(), pdf_file);
 }
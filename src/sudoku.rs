@@ -1,38 +1,267 @@
+pub mod api;
+pub mod candidates;
+pub mod clues;
+pub mod explain;
+pub mod format;
+pub mod game;
+pub mod gattai;
+#[cfg(feature = "generator")]
+pub mod generator;
+#[cfg(feature = "remote")]
 pub mod get;
+pub mod hint;
+pub mod rating;
+pub mod render;
+pub mod rpc;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod solver;
+pub mod symmetry;
+pub mod validate;
+pub mod variant;
 
-const SUDOKU_SIZE: usize = 9;
+/// The number of rows, columns and digits.
+pub const SUDOKU_SIZE: usize = 9;
 // Unfortunately f64.sqrt() is not const fn so BOX_SIZE cannot be computed
 // in compile time.
 const BOX_SIZE: usize = 3;
 
+/// Where the digit of a cell comes from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Origin {
+    /// The digit is part of the puzzle.
+    Given,
+    /// A player placed the digit.
+    User,
+    /// The solver placed the digit.
+    Solver,
+}
+
 #[derive(Debug, Copy, Clone)]
 struct Cell {
     value: u8,
-    num_iteration: u32,
+    origin: Origin,
 }
 
 type Cells = [[Option<Cell>; SUDOKU_SIZE]; SUDOKU_SIZE];
 
+/// A cell position in the grid. `x` is the column and `y` is the row, both
+/// starting from 0, the same way `Cells` is indexed. Note that the methods of
+/// `Sudoku` that take a row and a column take the row first; `row` and
+/// `column` read a position in that order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Position {
+    fn value(self, cells: &Cells) -> Option<u8> {
+        cells[self.x][self.y].map(|cell| cell.value)
+    }
+
+    /// The row from 0, the same as `y`.
+    pub fn row(self) -> usize {
+        self.y
+    }
+
+    /// The column from 0, the same as `x`.
+    pub fn column(self) -> usize {
+        self.x
+    }
+
+    /// Returns `true` if the cells share a border.
+    pub fn is_adjacent(self, other: Position) -> bool {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) == 1
+    }
+}
+
+/// A 9x9 grid. The public methods address cells by `row` and `column`, both
+/// from 0 to 8, row 0 being the top row and column 0 the leftmost column.
+/// Digits are 1 to 9, and the arrays of `from_rows` and `to_rows` use 0 for
+/// an empty cell. The methods that take a `Position` address the cell by
+/// its `x` column and `y` row.
+///
+/// Grids are equal when they have the same digits in the same cells. The
+/// origin of the digits is not compared, so a solved grid equals the same
+/// digits given as a puzzle.
 #[derive(Copy, Clone)]
 pub struct Sudoku {
     cells: Cells,
 }
 
+impl Sudoku {
+    /// A grid without digits.
+    pub fn empty() -> Sudoku {
+        Sudoku {
+            cells: [[None; SUDOKU_SIZE]; SUDOKU_SIZE],
+        }
+    }
+
+    /// A grid from its rows, 0 meaning an empty cell. The digits are givens.
+    pub fn from_rows(rows: [[u8; SUDOKU_SIZE]; SUDOKU_SIZE]) -> Result<Sudoku, String> {
+        let mut sudoku = Sudoku::empty();
+        for (row, digits) in rows.iter().enumerate() {
+            for (column, digit) in digits.iter().enumerate() {
+                match digit {
+                    0 => {}
+                    1..=9 => sudoku.set(row, column, *digit),
+                    _ => {
+                        return Err(format!(
+                            "invalid digit {} at row {} column {}",
+                            digit, row, column
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(sudoku)
+    }
+
+    /// The rows, 0 meaning an empty cell.
+    pub fn to_rows(&self) -> [[u8; SUDOKU_SIZE]; SUDOKU_SIZE] {
+        let mut rows = [[0; SUDOKU_SIZE]; SUDOKU_SIZE];
+        for (row, digits) in rows.iter_mut().enumerate() {
+            for (column, digit) in digits.iter_mut().enumerate() {
+                *digit = self.get(row, column).unwrap_or(0);
+            }
+        }
+        rows
+    }
+
+    /// The digit in a cell.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8.
+    pub fn get(&self, row: usize, column: usize) -> Option<u8> {
+        self.value(Position { x: column, y: row })
+    }
+
+    /// Writes a given digit into a cell.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8 or `digit` is not from
+    /// 1 to 9.
+    pub fn set(&mut self, row: usize, column: usize, digit: u8) {
+        assert!((1..=9).contains(&digit), "invalid digit {}", digit);
+        self.set_value(Position { x: column, y: row }, Some(digit));
+    }
+
+    /// Empties a cell.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8.
+    pub fn clear(&mut self, row: usize, column: usize) {
+        self.set_value(Position { x: column, y: row }, None);
+    }
+
+    /// Writes a digit of a player into a cell.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8 or `digit` is not from
+    /// 1 to 9.
+    pub fn place(&mut self, row: usize, column: usize, digit: u8) {
+        assert!((1..=9).contains(&digit), "invalid digit {}", digit);
+        self.set_cell(Position { x: column, y: row }, Some(digit), Origin::User);
+    }
+
+    /// `true` if the cell has a digit of the puzzle.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8.
+    pub fn is_given(&self, row: usize, column: usize) -> bool {
+        self.origin(row, column) == Some(Origin::Given)
+    }
+
+    /// Where the digit of a cell comes from, `None` for an empty cell.
+    ///
+    /// Panics if `row` or `column` is not from 0 to 8.
+    pub fn origin(&self, row: usize, column: usize) -> Option<Origin> {
+        self.cells[column][row].map(|cell| cell.origin)
+    }
+
+    /// The grid with only the givens, for example the puzzle after it was
+    /// solved.
+    pub fn givens(&self) -> Sudoku {
+        let mut givens = *self;
+        for cell in givens.cells.iter_mut().flatten() {
+            if cell.is_some_and(|cell| cell.origin != Origin::Given) {
+                *cell = None;
+            }
+        }
+        givens
+    }
+
+    /// The rows from the top, each from left to right.
+    pub fn rows(&self) -> impl Iterator<Item = [Option<u8>; SUDOKU_SIZE]> + '_ {
+        (0..SUDOKU_SIZE).map(move |row| array_init::array_init(|column| self.get(row, column)))
+    }
+
+    /// The columns from the left, each from top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = [Option<u8>; SUDOKU_SIZE]> + '_ {
+        (0..SUDOKU_SIZE).map(move |column| array_init::array_init(|row| self.get(row, column)))
+    }
+
+    /// The 3x3 boxes row by row from the top left box, each box row by row.
+    pub fn boxes(&self) -> impl Iterator<Item = [Option<u8>; SUDOKU_SIZE]> + '_ {
+        (0..SUDOKU_SIZE).map(move |b| {
+            array_init::array_init(|i| {
+                let row = (b / BOX_SIZE) * BOX_SIZE + i / BOX_SIZE;
+                let column = (b % BOX_SIZE) * BOX_SIZE + i % BOX_SIZE;
+                self.get(row, column)
+            })
+        })
+    }
+
+    /// The `(row, column)` of the empty cells row by row.
+    pub fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..SUDOKU_SIZE * SUDOKU_SIZE)
+            .map(|i| (i / SUDOKU_SIZE, i % SUDOKU_SIZE))
+            .filter(move |(row, column)| self.get(*row, *column).is_none())
+    }
+
+    /// The digit in the cell at `position`, like `get` with the row
+    /// `position.y` and the column `position.x`.
+    ///
+    /// Panics if `position` is not in the grid.
+    pub fn value(&self, position: Position) -> Option<u8> {
+        position.value(&self.cells)
+    }
+
+    /// Places `value` to `position` as a given, or empties the cell with
+    /// `None`.
+    ///
+    /// Panics if `position` is not in the grid or `value` is not from 1 to 9.
+    pub fn set_value(&mut self, position: Position, value: Option<u8>) {
+        self.set_cell(position, value, Origin::Given);
+    }
+
+    /// Places `value` to `position` with its origin, or empties the cell with
+    /// `None`.
+    ///
+    /// Panics if `position` is not in the grid or `value` is not from 1 to 9.
+    pub fn set_cell(&mut self, position: Position, value: Option<u8>, origin: Origin) {
+        if let Some(value) = value {
+            assert!((1..=9).contains(&value), "invalid digit {}", value);
+        }
+        self.cells[position.x][position.y] = value.map(|value| Cell { value, origin });
+    }
+}
+
 impl std::fmt::Display for Sudoku {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(fmt, "").unwrap();
+        writeln!(fmt)?;
         for y in 0..SUDOKU_SIZE {
             for x in 0..SUDOKU_SIZE {
                 let to_write = match self.cells[x][y] {
                     None => String::from(" "),
                     Some(cell) => cell.value.to_string(),
                 };
-                write!(fmt, "|{}", to_write).unwrap()
+                write!(fmt, "|{}", to_write)?
             }
-            writeln!(fmt, "|").unwrap();
+            writeln!(fmt, "|")?;
         }
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -42,13 +271,90 @@ impl std::fmt::Debug for Sudoku {
     }
 }
 
+/// Cells are equal if they have the same digit, wherever it comes from.
 impl PartialEq for Cell {
     fn eq(&self, other: &Self) -> bool {
-        return self.value == other.value;
+        self.value == other.value
     }
 }
 impl PartialEq for Sudoku {
     fn eq(&self, other: &Self) -> bool {
-        return self.cells == other.cells;
+        self.cells == other.cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_and_columns() {
+        let mut rows = [[0; 9]; 9];
+        rows[0] = [5, 3, 0, 0, 7, 0, 0, 0, 0];
+        rows[1][0] = 6;
+        let mut sudoku = Sudoku::from_rows(rows).unwrap();
+        assert_eq!(sudoku.get(0, 1), Some(3));
+        assert_eq!(sudoku.get(1, 0), Some(6));
+        let position = Position { x: 1, y: 0 };
+        assert_eq!((position.row(), position.column()), (0, 1));
+        assert_eq!(sudoku.value(position), Some(3));
+        assert!(sudoku.is_given(0, 0));
+        assert!(!sudoku.is_given(0, 2));
+        assert_eq!(sudoku.to_rows(), rows);
+
+        sudoku.set(8, 7, 4);
+        sudoku.clear(0, 0);
+        assert_eq!(sudoku.get(8, 7), Some(4));
+        assert_eq!(sudoku.get(0, 0), None);
+
+        let first_row = sudoku.rows().next().unwrap();
+        assert_eq!(first_row[1], Some(3));
+        let first_column = sudoku.columns().next().unwrap();
+        assert_eq!(first_column[1], Some(6));
+        let last_box = sudoku.boxes().last().unwrap();
+        assert_eq!(last_box[7], Some(4));
+        assert_eq!(sudoku.empty_cells().count(), 81 - 4);
+        assert_eq!(sudoku.empty_cells().next(), Some((0, 0)));
+
+        rows[4][4] = 10;
+        assert!(Sudoku::from_rows(rows).is_err());
+        assert_eq!(Sudoku::empty().to_rows(), [[0; 9]; 9]);
+    }
+
+    #[test]
+    fn test_origin() {
+        let mut sudoku = Sudoku::empty();
+        sudoku.set(0, 0, 1);
+        sudoku.place(0, 1, 2);
+        assert!(solver::solve(&mut sudoku));
+        assert!(sudoku.empty_cells().next().is_none());
+        assert_eq!(sudoku.origin(0, 0), Some(Origin::Given));
+        assert_eq!(sudoku.origin(0, 1), Some(Origin::User));
+        assert_eq!(sudoku.origin(0, 2), Some(Origin::Solver));
+        assert!(sudoku.is_given(0, 0));
+        assert!(!sudoku.is_given(0, 1));
+
+        let givens = sudoku.givens();
+        assert_eq!(givens.get(0, 0), Some(1));
+        assert_eq!(givens.empty_cells().count(), 80);
+
+        // Equality compares the digits only.
+        let mut given = sudoku;
+        given.set(0, 1, 2);
+        assert_eq!(given.origin(0, 1), Some(Origin::Given));
+        assert_eq!(given, sudoku);
+    }
+
+    #[test]
+    fn test_unsolvable_grid_is_left_unchanged() {
+        let mut sudoku = Sudoku::empty();
+        for column in 0..8 {
+            sudoku.set(0, column, column as u8 + 1);
+        }
+        sudoku.set(1, 8, 9);
+        let puzzle = sudoku;
+        assert!(!solver::solve(&mut sudoku));
+        assert_eq!(sudoku, puzzle);
+        assert_eq!(sudoku.empty_cells().count(), 72);
     }
 }
@@ -0,0 +1,364 @@
+//! JSON operations for the frontends that are not written in Rust. The grids
+//! use the `SudokuResponse` shape of the cs.utep.edu puzzle service, so this
+//! crate can serve the same JSON as the service.
+
+#[cfg(feature = "generator")]
+use super::generator;
+use super::hint;
+use super::rating;
+#[cfg(feature = "generator")]
+use super::rating::Difficulty;
+#[cfg(feature = "generator")]
+use super::symmetry::Symmetry;
+use super::variant::Puzzle;
+use super::{solver, validate, Position, Sudoku, SUDOKU_SIZE};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+#[cfg(feature = "generator")]
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A filled cell, `x` being the column and `y` the row, both starting from 0.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CellResponse {
+    pub x: usize,
+    pub y: usize,
+    pub value: u8,
+}
+
+/// A grid as sent by the puzzle service: the size is a string and only the
+/// filled cells are listed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SudokuResponse {
+    pub response: bool,
+    #[serde(serialize_with = "to_string", deserialize_with = "from_str")]
+    pub size: u8,
+    pub squares: Vec<CellResponse>,
+}
+
+impl SudokuResponse {
+    pub fn from_sudoku(sudoku: &Sudoku) -> SudokuResponse {
+        let mut squares = Vec::new();
+        for y in 0..SUDOKU_SIZE {
+            for x in 0..SUDOKU_SIZE {
+                if let Some(value) = sudoku.value(Position { x, y }) {
+                    squares.push(CellResponse { x, y, value });
+                }
+            }
+        }
+        SudokuResponse {
+            response: true,
+            size: SUDOKU_SIZE as u8,
+            squares,
+        }
+    }
+
+    pub fn to_sudoku(&self) -> Result<Sudoku, String> {
+        if !self.response || self.size as usize != SUDOKU_SIZE {
+            return Err(format!("Unexpected response for size {}", self.size));
+        }
+
+        let mut sudoku = Sudoku::empty();
+        for cell in self.squares.iter() {
+            if cell.x >= SUDOKU_SIZE || cell.y >= SUDOKU_SIZE || !(1..=9).contains(&cell.value) {
+                return Err(format!(
+                    "invalid square x {} y {} value {}",
+                    cell.x, cell.y, cell.value
+                ));
+            }
+            let position = Position {
+                x: cell.x,
+                y: cell.y,
+            };
+            sudoku.set_value(position, Some(cell.value));
+        }
+        Ok(sudoku)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// There is no operation with the name.
+    UnknownMethod(String),
+    /// The parameters are missing or malformed.
+    InvalidParams(String),
+    /// The puzzle has no solution or the operation could not be done for it.
+    Failed(String),
+}
+
+impl Error {
+    /// The JSON-RPC error code: the standard codes for an unknown method and
+    /// invalid parameters, and 1 for a failed operation.
+    pub fn code(&self) -> i64 {
+        match self {
+            Error::UnknownMethod(_) => -32601,
+            Error::InvalidParams(_) => -32602,
+            Error::Failed(_) => 1,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownMethod(method) => write!(fmt, "unknown method '{}'", method),
+            Error::InvalidParams(message) | Error::Failed(message) => write!(fmt, "{}", message),
+        }
+    }
+}
+
+/// The grid parameter of an operation: either the squares of the service
+/// format or a puzzle in the puzzle file format, which may have constraints.
+#[derive(Deserialize)]
+struct GridParams {
+    #[serde(default)]
+    squares: Option<Vec<CellResponse>>,
+    #[serde(default)]
+    puzzle: Option<String>,
+}
+
+/// Runs the operation `method` with the JSON object `params`. The operations
+/// are `solve`, `validate`, `rate` and `hint`, which take a grid, and
+/// `generate`, which takes a `level` from 1 to 3 like the puzzle service and
+/// optionally a `symmetry` such as `rotational`. `generate` needs the
+/// `generator` feature and is an unknown method without it.
+pub fn call(method: &str, params: &Value) -> Result<Value, Error> {
+    match method {
+        "solve" => solve(params),
+        "validate" => validate(params),
+        "rate" => rate(params),
+        "hint" => hint(params),
+        #[cfg(feature = "generator")]
+        "generate" => generate(params),
+        _ => Err(Error::UnknownMethod(method.to_string())),
+    }
+}
+
+fn solve(params: &Value) -> Result<Value, Error> {
+    let mut puzzle = puzzle(params)?;
+    solver::try_solve(&mut puzzle)
+        .map_err(|contradiction| Error::Failed(format!("no solution: {}", contradiction)))?;
+    Ok(to_value(SudokuResponse::from_sudoku(&puzzle.sudoku)))
+}
+
+fn validate(params: &Value) -> Result<Value, Error> {
+    let puzzle = puzzle(params)?;
+    let conflicts: Vec<Value> = validate::validate(&puzzle.sudoku)
+        .iter()
+        .map(|conflict| {
+            json!({
+                "house": conflict.house.to_string(),
+                "value": conflict.value,
+                "squares": conflict.cells.iter().map(|position| CellResponse {
+                    x: position.x,
+                    y: position.y,
+                    value: conflict.value,
+                }).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let solutions = solver::count_solutions(&puzzle, 2);
+    Ok(json!({
+        "response": true,
+        "solutions": solutions,
+        "unique": solutions == 1,
+        "conflicts": conflicts,
+    }))
+}
+
+fn rate(params: &Value) -> Result<Value, Error> {
+    let puzzle = puzzle(params)?;
+    let rating = rating::rate(&puzzle)
+        .ok_or_else(|| Error::Failed("the puzzle has no solution".to_string()))?;
+    Ok(json!({
+        "response": true,
+        "level": rating.difficulty.level(),
+        "difficulty": rating.difficulty.to_string(),
+        "naked_singles": rating.naked_singles,
+        "hidden_singles": rating.hidden_singles,
+        "needs_guessing": rating.needs_guessing,
+    }))
+}
+
+fn hint(params: &Value) -> Result<Value, Error> {
+    let puzzle = puzzle(params)?;
+    let hint = hint::hint(&puzzle)
+        .ok_or_else(|| Error::Failed("no hint, the grid is full or has no solution".to_string()))?;
+    Ok(json!({
+        "response": true,
+        "square": CellResponse {
+            x: hint.position.x,
+            y: hint.position.y,
+            value: hint.value,
+        },
+        "technique": hint.technique.to_string(),
+        "message": hint.to_string(),
+    }))
+}
+
+#[cfg(feature = "generator")]
+fn generate(params: &Value) -> Result<Value, Error> {
+    if let Some(size) = params.get("size") {
+        if number(size) != Some(SUDOKU_SIZE as u64) {
+            return Err(Error::InvalidParams(format!(
+                "only size {} is supported",
+                SUDOKU_SIZE
+            )));
+        }
+    }
+    let difficulty = match params.get("level") {
+        None => Difficulty::Medium,
+        Some(level) => number(level)
+            .and_then(|level| u8::try_from(level).ok())
+            .and_then(Difficulty::from_level)
+            .ok_or_else(|| Error::InvalidParams("level must be 1, 2 or 3".to_string()))?,
+    };
+    let symmetry: Symmetry = match params.get("symmetry") {
+        None => Symmetry::None,
+        Some(symmetry) => symmetry
+            .as_str()
+            .ok_or_else(|| Error::InvalidParams("symmetry must be a string".to_string()))?
+            .parse()
+            .map_err(Error::InvalidParams)?,
+    };
+    Ok(to_value(SudokuResponse::from_sudoku(
+        &generator::generate_symmetric(difficulty, symmetry),
+    )))
+}
+
+fn puzzle(params: &Value) -> Result<Puzzle, Error> {
+    let params: GridParams =
+        serde_json::from_value(params.clone()).map_err(|e| Error::InvalidParams(e.to_string()))?;
+    match (params.squares, params.puzzle) {
+        (Some(squares), None) => {
+            let response = SudokuResponse {
+                response: true,
+                size: SUDOKU_SIZE as u8,
+                squares,
+            };
+            let sudoku = response.to_sudoku().map_err(Error::InvalidParams)?;
+            Ok(Puzzle {
+                sudoku,
+                constraints: Vec::new(),
+            })
+        }
+        (None, Some(puzzle)) => puzzle.parse().map_err(Error::InvalidParams),
+        _ => Err(Error::InvalidParams(
+            "expected either squares or puzzle".to_string(),
+        )),
+    }
+}
+
+/// A number given as a JSON number or, like in the query of the puzzle
+/// service, as a string.
+#[cfg(feature = "generator")]
+fn number(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_value(response: SudokuResponse) -> Value {
+    serde_json::to_value(response).unwrap()
+}
+
+/// Writes a number as a JSON string like the puzzle service does with `size`.
+fn to_string<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+/// Reads a value that is written as a JSON string, like the `"size": "9"` of
+/// the puzzle service, by parsing the string with `FromStr`. A string that
+/// does not parse is a deserialization error. See
+/// https://github.com/serde-rs/json/issues/317
+fn from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    T::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n\
+                          7...2...6\n.6....28.\n...419..5\n....8..79\n";
+
+    #[test]
+    fn test_sudoku_response() {
+        let json = r#"{"response":true,"size":"9","squares":[{"x":2,"y":0,"value":4}]}"#;
+        let response: SudokuResponse = serde_json::from_str(json).unwrap();
+        let sudoku = response.to_sudoku().unwrap();
+        assert_eq!(sudoku.value(Position { x: 2, y: 0 }), Some(4));
+        assert_eq!(serde_json::to_string(&response).unwrap(), json);
+        assert_eq!(SudokuResponse::from_sudoku(&sudoku), response);
+    }
+
+    #[test]
+    fn test_call() {
+        let params = json!({ "puzzle": PUZZLE });
+        let solved = call("solve", &params).unwrap();
+        assert_eq!(solved["squares"].as_array().unwrap().len(), 81);
+        assert_eq!(solved["size"], "9");
+
+        let squares = call("solve", &json!({ "squares": solved["squares"] })).unwrap();
+        assert_eq!(squares, solved);
+
+        assert_eq!(call("validate", &params).unwrap()["unique"], true);
+        let duplicate = json!({ "puzzle": format!("5.5{}", ".".repeat(78)) });
+        let validated = call("validate", &duplicate).unwrap();
+        assert_eq!(validated["solutions"], 0);
+        assert_eq!(validated["conflicts"][0]["house"], "row 1");
+        assert_eq!(
+            call("solve", &duplicate),
+            Err(Error::Failed(
+                "no solution: 5 is more than once in row 1: r1c1 r1c3".to_string()
+            ))
+        );
+        assert_eq!(call("rate", &params).unwrap()["needs_guessing"], false);
+        let hint = call("hint", &params).unwrap();
+        assert!(hint["square"]["value"].is_u64());
+
+        assert_eq!(
+            call("solve", &json!({})),
+            Err(Error::InvalidParams(
+                "expected either squares or puzzle".to_string()
+            ))
+        );
+        assert_eq!(
+            call("shuffle", &params),
+            Err(Error::UnknownMethod("shuffle".to_string()))
+        );
+    }
+
+    #[cfg(feature = "generator")]
+    #[test]
+    fn test_call_generate() {
+        assert_eq!(
+            call("generate", &json!({ "level": "4" })),
+            Err(Error::InvalidParams("level must be 1, 2 or 3".to_string()))
+        );
+        // 257 would be 1 if it was cut to a byte.
+        assert_eq!(
+            call("generate", &json!({ "level": 257 })),
+            Err(Error::InvalidParams("level must be 1, 2 or 3".to_string()))
+        );
+        assert_eq!(
+            call("generate", &json!({ "symmetry": "spiral" })),
+            Err(Error::InvalidParams(
+                "unknown symmetry 'spiral'".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! A grid of candidates (pencil marks), either derived from the rules or
+//! edited by hand.
+
+use super::variant::Puzzle;
+use super::{solver, Position, Sudoku, SUDOKU_SIZE};
+use std::fmt;
+use std::str::FromStr;
+
+/// The candidates of every cell, indexed `[x][y]` like `Cells`. Bit `d - 1`
+/// of a cell is set when `d` is a candidate.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct CandidateGrid {
+    cells: [[u16; SUDOKU_SIZE]; SUDOKU_SIZE],
+}
+
+impl CandidateGrid {
+    /// A grid without any candidate.
+    pub fn empty() -> CandidateGrid {
+        CandidateGrid {
+            cells: [[0; SUDOKU_SIZE]; SUDOKU_SIZE],
+        }
+    }
+
+    /// The candidates of a classic Sudoku: the digits of the filled cells and
+    /// the digits not yet in the row, column or box of the empty cells.
+    pub fn from_sudoku(sudoku: &Sudoku) -> CandidateGrid {
+        CandidateGrid::from_puzzle(&Puzzle {
+            sudoku: *sudoku,
+            constraints: Vec::new(),
+        })
+    }
+
+    /// Like `from_sudoku` but the candidates also respect the constraints of
+    /// the puzzle.
+    pub fn from_puzzle(puzzle: &Puzzle) -> CandidateGrid {
+        let mut grid = CandidateGrid::empty();
+        for x in 0..SUDOKU_SIZE {
+            for y in 0..SUDOKU_SIZE {
+                let position = Position { x, y };
+                match puzzle.sudoku.value(position) {
+                    Some(value) => grid.insert(position, value),
+                    None => grid.set(position, &solver::candidates(puzzle, x, y)),
+                }
+            }
+        }
+        grid
+    }
+
+    /// The candidates of a cell in increasing order.
+    pub fn get(&self, position: Position) -> Vec<u8> {
+        (1..=SUDOKU_SIZE as u8)
+            .filter(|value| self.contains(position, *value))
+            .collect()
+    }
+
+    /// Panics if `value` is not from 1 to 9.
+    pub fn contains(&self, position: Position, value: u8) -> bool {
+        self.cells[position.x][position.y] & bit(value) != 0
+    }
+
+    pub fn count(&self, position: Position) -> usize {
+        self.cells[position.x][position.y].count_ones() as usize
+    }
+
+    /// Replaces the candidates of a cell.
+    ///
+    /// Panics if any of `values` is not from 1 to 9.
+    pub fn set(&mut self, position: Position, values: &[u8]) {
+        self.cells[position.x][position.y] =
+            values.iter().fold(0, |mask, value| mask | bit(*value));
+    }
+
+    /// Panics if `value` is not from 1 to 9.
+    pub fn insert(&mut self, position: Position, value: u8) {
+        self.cells[position.x][position.y] |= bit(value);
+    }
+
+    /// Removes a candidate. Returns `true` if the cell had it.
+    ///
+    /// Panics if `value` is not from 1 to 9.
+    pub fn remove(&mut self, position: Position, value: u8) -> bool {
+        let had = self.contains(position, value);
+        self.cells[position.x][position.y] &= !bit(value);
+        had
+    }
+
+    /// Adds the candidate if it is missing, removes it otherwise.
+    ///
+    /// Panics if `value` is not from 1 to 9.
+    pub fn toggle(&mut self, position: Position, value: u8) {
+        self.cells[position.x][position.y] ^= bit(value);
+    }
+
+    pub fn clear(&mut self, position: Position) {
+        self.cells[position.x][position.y] = 0;
+    }
+
+    /// `true` if no cell has a candidate.
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().flatten().all(|mask| *mask == 0)
+    }
+}
+
+fn bit(value: u8) -> u16 {
+    assert!(
+        (1..=SUDOKU_SIZE as u8).contains(&value),
+        "invalid digit {}",
+        value
+    );
+    1 << (value - 1)
+}
+
+/// Writes the standard 729 character form: nine characters per cell in
+/// row-major order, the digit if it is a candidate and `0` otherwise.
+impl fmt::Display for CandidateGrid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..SUDOKU_SIZE {
+            for x in 0..SUDOKU_SIZE {
+                for value in 1..=SUDOKU_SIZE as u8 {
+                    let c = if self.contains(Position { x, y }, value) {
+                        (b'0' + value) as char
+                    } else {
+                        '0'
+                    };
+                    write!(fmt, "{}", c)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CandidateGrid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "CandidateGrid({})", self)
+    }
+}
+
+/// Parses the 729 character form. Missing candidates may be written as `0`
+/// or `.`, and whitespace is ignored.
+impl FromStr for CandidateGrid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if chars.len() != SUDOKU_SIZE * SUDOKU_SIZE * SUDOKU_SIZE {
+            return Err(format!(
+                "expected 729 candidate characters, got {}",
+                chars.len()
+            ));
+        }
+        let mut grid = CandidateGrid::empty();
+        for (i, c) in chars.iter().enumerate() {
+            let cell = i / SUDOKU_SIZE;
+            let position = Position {
+                x: cell % SUDOKU_SIZE,
+                y: cell / SUDOKU_SIZE,
+            };
+            let expected = (i % SUDOKU_SIZE) as u8 + 1;
+            match c {
+                '0' | '.' => {}
+                _ if c.to_digit(10) == Some(expected as u32) => grid.insert(position, expected),
+                _ => {
+                    return Err(format!(
+                        "invalid candidate '{}' at {}, expected {} or 0",
+                        c, position, expected
+                    ))
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_sudoku() {
+        let puzzle: Puzzle = format!("12345678.{}", ".".repeat(72)).parse().unwrap();
+        let grid = CandidateGrid::from_sudoku(&puzzle.sudoku);
+        assert_eq!(grid.get(Position { x: 0, y: 0 }), vec![1]);
+        assert_eq!(grid.get(Position { x: 8, y: 0 }), vec![9]);
+        assert_eq!(grid.get(Position { x: 0, y: 1 }), vec![4, 5, 6, 7, 8, 9]);
+        assert_eq!(grid.count(Position { x: 8, y: 1 }), 7);
+        assert_eq!(grid.count(Position { x: 8, y: 8 }), 9);
+    }
+
+    #[test]
+    fn test_edit_and_format() {
+        let mut grid = CandidateGrid::empty();
+        assert!(grid.is_empty());
+        grid.set(Position { x: 1, y: 0 }, &[2, 6]);
+        grid.toggle(Position { x: 1, y: 0 }, 6);
+        grid.toggle(Position { x: 1, y: 0 }, 9);
+        assert!(grid.remove(Position { x: 1, y: 0 }, 2));
+        assert!(!grid.remove(Position { x: 1, y: 0 }, 2));
+        grid.insert(Position { x: 8, y: 8 }, 1);
+
+        let text = grid.to_string();
+        assert_eq!(text.len(), 729);
+        assert_eq!(&text[..18], "000000000000000009");
+        assert_eq!(&text[720..], "100000000");
+        assert_eq!(text.parse(), Ok(grid));
+        assert_eq!(text.replace('0', ".").parse(), Ok(grid));
+        assert!("123".parse::<CandidateGrid>().is_err());
+        assert!(text.replacen("9", "8", 1).parse::<CandidateGrid>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid digit 10")]
+    fn test_invalid_digit() {
+        CandidateGrid::empty().insert(Position { x: 0, y: 0 }, 10);
+    }
+}
@@ -0,0 +1,104 @@
+//! Analysis of the givens of a puzzle with a unique solution.
+
+use super::variant::Puzzle;
+use super::{solver, Position, Sudoku, SUDOKU_SIZE};
+
+/// The givens that can be removed one at a time without losing the unique
+/// solution, row by row. Removing two of them together may still lose it.
+/// Empty if the puzzle does not have a unique solution. Digits that are not
+/// givens are kept.
+pub fn redundant_clues(sudoku: &Sudoku) -> Vec<Position> {
+    let mut puzzle = classic(sudoku);
+    if solver::count_solutions(&puzzle, 2) != 1 {
+        return Vec::new();
+    }
+    givens(sudoku)
+        .filter(|position| is_redundant(&mut puzzle, *position))
+        .collect()
+}
+
+/// `true` if the puzzle has a unique solution and loses it without any one
+/// of its givens.
+pub fn is_minimal(sudoku: &Sudoku) -> bool {
+    let mut puzzle = classic(sudoku);
+    solver::count_solutions(&puzzle, 2) == 1
+        && givens(sudoku).all(|position| !is_redundant(&mut puzzle, position))
+}
+
+fn classic(sudoku: &Sudoku) -> Puzzle {
+    Puzzle {
+        sudoku: *sudoku,
+        constraints: Vec::new(),
+    }
+}
+
+fn givens(sudoku: &Sudoku) -> impl Iterator<Item = Position> + '_ {
+    (0..SUDOKU_SIZE * SUDOKU_SIZE)
+        .map(|i| Position {
+            x: i % SUDOKU_SIZE,
+            y: i / SUDOKU_SIZE,
+        })
+        .filter(move |position| sudoku.is_given(position.y, position.x))
+}
+
+/// Checks the puzzle without the given at `position` and puts it back.
+fn is_redundant(puzzle: &mut Puzzle, position: Position) -> bool {
+    let value = puzzle.sudoku.value(position);
+    let origin = puzzle.sudoku.origin(position.y, position.x).unwrap();
+    puzzle.sudoku.set_cell(position, None, origin);
+    let is_redundant = solver::count_solutions(puzzle, 2) == 1;
+    puzzle.sudoku.set_cell(position, value, origin);
+    is_redundant
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "generator")]
+    use super::super::generator;
+    #[cfg(feature = "generator")]
+    use super::super::rating::Difficulty;
+    use super::*;
+    #[cfg(feature = "generator")]
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_redundant_clues() {
+        let mut solution = Sudoku::empty();
+        assert!(redundant_clues(&solution).is_empty());
+        assert!(!is_minimal(&solution));
+
+        // The digits of the solver are not clues.
+        solver::solve(&mut solution);
+        assert!(redundant_clues(&solution).is_empty());
+
+        let mut givens = Sudoku::empty();
+        for (row, values) in solution.rows().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                givens.set(row, column, value.unwrap());
+            }
+        }
+        assert_eq!(redundant_clues(&givens).len(), 81);
+        assert!(!is_minimal(&givens));
+    }
+
+    #[cfg(feature = "generator")]
+    #[test]
+    fn test_generated_puzzle_is_minimal() {
+        // The generator removes every clue it can for a hard puzzle.
+        let mut sudoku = generator::generate_with(&mut StdRng::seed_from_u64(3), Difficulty::Hard);
+        assert!(is_minimal(&sudoku));
+        assert!(redundant_clues(&sudoku).is_empty());
+
+        let (row, column) = sudoku.empty_cells().next().unwrap();
+        sudoku.set(row, column, solution_value(&sudoku, row, column));
+        assert!(!is_minimal(&sudoku));
+        assert!(redundant_clues(&sudoku).contains(&Position { x: column, y: row }));
+    }
+
+    #[cfg(feature = "generator")]
+    fn solution_value(sudoku: &Sudoku, row: usize, column: usize) -> u8 {
+        let mut solution = *sudoku;
+        solver::solve(&mut solution);
+        solution.get(row, column).unwrap()
+    }
+}
@@ -0,0 +1,102 @@
+//! Explanations of why a puzzle has no solution.
+
+use super::validate;
+use super::variant::Puzzle;
+use super::{solver, Origin, Position, SUDOKU_SIZE};
+
+/// The givens that together leave the puzzle without a solution, row by row,
+/// or `None` if the puzzle has a solution. The set is minimal: without any
+/// one of them the rest of the givens can be solved. The constraints of a
+/// variant puzzle and the digits that are not givens, such as the ones of a
+/// player, are always kept, so the set is empty if they alone have no
+/// solution.
+///
+/// The set is found by deleting the givens one at a time and putting back
+/// the ones whose deletion makes the puzzle solvable, so it takes a solver
+/// run for every given.
+pub fn unsolvable_givens(puzzle: &Puzzle) -> Option<Vec<Position>> {
+    if solver::count_solutions(puzzle, 1) > 0 {
+        return None;
+    }
+
+    let is_given =
+        |position: &Position| puzzle.sudoku.origin(position.y, position.x) == Some(Origin::Given);
+
+    // Two equal givens in a house are the smallest possible explanation.
+    for conflict in validate::validate(&puzzle.sudoku) {
+        let mut cells: Vec<Position> = conflict.cells.into_iter().filter(is_given).collect();
+        if cells.len() >= 2 {
+            cells.truncate(2);
+            cells.sort_by_key(|position| (position.y, position.x));
+            return Some(cells);
+        }
+    }
+
+    let mut core = puzzle.clone();
+    let mut givens = Vec::new();
+    for y in 0..SUDOKU_SIZE {
+        for x in 0..SUDOKU_SIZE {
+            let position = Position { x, y };
+            if !is_given(&position) {
+                continue;
+            }
+            let value = puzzle.sudoku.value(position);
+            core.sudoku.set_cell(position, None, Origin::Given);
+            if solver::count_solutions(&core, 1) > 0 {
+                core.sudoku.set_cell(position, value, Origin::Given);
+                givens.push(position);
+            }
+        }
+    }
+    Some(givens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Sudoku;
+    use super::*;
+
+    #[test]
+    fn test_unsolvable_givens() {
+        let solvable: Puzzle = ".".repeat(81).parse().unwrap();
+        assert_eq!(unsolvable_givens(&solvable), None);
+
+        let conflict: Puzzle = format!("5...5....{}", ".".repeat(72)).parse().unwrap();
+        assert_eq!(
+            unsolvable_givens(&conflict),
+            Some(vec![Position { x: 0, y: 0 }, Position { x: 4, y: 0 }])
+        );
+
+        // Even without the 7 and the 8 the first row has no place for 9,
+        // because the 9 at r2c9 is in the same box as r1c7 to r1c9. The 7 at
+        // r5c5 has nothing to do with it.
+        let puzzle: Puzzle = format!(
+            "12345678.\n........9\n{}\n....7....\n{}",
+            ".".repeat(18),
+            ".".repeat(36)
+        )
+        .parse()
+        .unwrap();
+        let mut expected: Vec<Position> = (0..6).map(|x| Position { x, y: 0 }).collect();
+        expected.push(Position { x: 8, y: 1 });
+        assert_eq!(unsolvable_givens(&puzzle), Some(expected.clone()));
+
+        // A digit of a player is kept and not reported.
+        let mut played = puzzle.clone();
+        played.sudoku.clear(4, 4);
+        played.sudoku.place(4, 4, 7);
+        assert_eq!(unsolvable_givens(&played), Some(expected));
+
+        let mut conflict = Puzzle {
+            sudoku: Sudoku::empty(),
+            constraints: Vec::new(),
+        };
+        // A given in conflict with a digit of a player is reported alone.
+        conflict.sudoku.set(0, 0, 5);
+        conflict.sudoku.place(0, 4, 5);
+        assert_eq!(
+            unsolvable_givens(&conflict),
+            Some(vec![Position { x: 0, y: 0 }])
+        );
+    }
+}
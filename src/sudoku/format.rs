@@ -0,0 +1,535 @@
+//! The puzzle file format.
+//!
+//! ```text
+//! # Lines starting with '#' are comments.
+//! # The grid is nine rows of nine characters, '.' or '0' is an empty cell.
+//! ..3...6..
+//! .4.7.8.3.
+//! ...
+//! # Constraints come after the grid, one per line. Cells are written as
+//! # r<row>c<column> and both start from 1.
+//! thermo r1c1 r1c2 r1c3
+//! arrow r5c5 r4c5 r3c5
+//! whispers r9c1 r8c2 r7c3
+//! renban r2c7 r2c8 r2c9
+//! palindrome r6c1 r6c2 r6c3 r6c4
+//! # Markers between two adjacent cells.
+//! white r1c1 r1c2
+//! black r1c1 r2c1
+//! x r3c3 r3c4
+//! v r3c3 r4c3
+//! greater r5c5 r5c6
+//! # The relation does not hold where there is no marker.
+//! negative white
+//! negative x
+//! # Clues outside the grid are written as <side> <row or column> <clue>.
+//! sandwich top 3 15
+//! skyscraper left 1 4
+//! xsum right 9 21
+//! # Cells that must be even, odd or one of the listed digits.
+//! even r1c1 r1c2
+//! odd r9c9
+//! digits 137 r5c5
+//! # A little killer clue is the first cell of the diagonal, the direction
+//! # (dr, dl, ur or ul) and the sum. The first cell is on the edge of the
+//! # grid that the diagonal comes from.
+//! littlekiller r1c2 dr 40
+//! ```
+//!
+//! The bulb of a thermometer, the circle of an arrow and the greater cell of
+//! a greater-than sign are the first cells of their lines. `nonconsecutive`
+//! is the same as `negative white`.
+
+use super::variant::{
+    Allowed, Arrow, CellRestriction, Constraint, Direction, Dot, DotKind, GermanWhispers,
+    GreaterThan, LittleKiller, NegativeDots, Outside, Palindrome, Puzzle, Renban, Sandwich, Side,
+    Skyscraper, Thermometer, XSum,
+};
+use super::{Cell, Cells, Origin, Position, Sudoku, SUDOKU_SIZE};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+impl FromStr for Puzzle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid: Vec<Option<u8>> = Vec::with_capacity(SUDOKU_SIZE * SUDOKU_SIZE);
+        let mut constraints = Vec::new();
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let result = if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                parse_constraint(line).map(|constraint| constraints.push(constraint))
+            } else {
+                parse_row(line, &mut grid)
+            };
+            result.map_err(|e| format!("line {}: {}", line_number + 1, e))?;
+        }
+
+        if grid.len() != SUDOKU_SIZE * SUDOKU_SIZE {
+            return Err(format!(
+                "expected {} cells in the grid, found {}",
+                SUDOKU_SIZE * SUDOKU_SIZE,
+                grid.len()
+            ));
+        }
+
+        let mut cells: Cells = [[None; SUDOKU_SIZE]; SUDOKU_SIZE];
+        for (index, value) in grid.into_iter().enumerate() {
+            cells[index % SUDOKU_SIZE][index / SUDOKU_SIZE] = value.map(|value| Cell {
+                value,
+                origin: Origin::Given,
+            });
+        }
+
+        Ok(Puzzle {
+            sudoku: Sudoku { cells },
+            constraints,
+        })
+    }
+}
+
+fn parse_row(line: &str, grid: &mut Vec<Option<u8>>) -> Result<(), String> {
+    for c in line.chars().filter(|c| !c.is_whitespace()) {
+        let value = match c {
+            '.' | '0' => None,
+            '1'..='9' => Some(c as u8 - b'0'),
+            _ => return Err(format!("unexpected character '{}' in the grid", c)),
+        };
+        grid.push(value);
+    }
+    Ok(())
+}
+
+fn parse_constraint(line: &str) -> Result<Constraint, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+
+    match name {
+        "nonconsecutive" => return Ok(negative(DotKind::White)),
+        "negative" => {
+            return match words.next().and_then(parse_dot_kind) {
+                Some(kind) => Ok(negative(kind)),
+                None => Err(String::from(
+                    "'negative' needs a marker: white, black, x or v",
+                )),
+            }
+        }
+        "sandwich" | "skyscraper" | "xsum" => {
+            let words: Vec<&str> = words.collect();
+            let (location, value) = match words[..] {
+                [side, index, value] => (parse_outside(side, index)?, parse_number(value)?),
+                _ => {
+                    return Err(format!(
+                        "'{}' needs a side, a row or column and a clue",
+                        name
+                    ))
+                }
+            };
+            return Ok(match name {
+                "sandwich" => Constraint::Sandwich(Sandwich {
+                    location,
+                    sum: value,
+                }),
+                "skyscraper" => Constraint::Skyscraper(Skyscraper {
+                    location,
+                    count: value,
+                }),
+                _ => Constraint::XSum(XSum {
+                    location,
+                    sum: value,
+                }),
+            });
+        }
+        "digits" => {
+            let words: Vec<&str> = words.collect();
+            let digits = match words.first() {
+                Some(digits) => parse_digits(digits)?,
+                None => return Err(String::from("'digits' needs the allowed digits")),
+            };
+            let cells = words[1..]
+                .iter()
+                .map(|word| parse_position(word))
+                .collect::<Result<Vec<_>, _>>()?;
+            return restriction(name, cells, Allowed::Digits(digits));
+        }
+        "even" | "odd" => {
+            let cells = words.map(parse_position).collect::<Result<Vec<_>, _>>()?;
+            let allowed = if name == "even" {
+                Allowed::Even
+            } else {
+                Allowed::Odd
+            };
+            return restriction(name, cells, allowed);
+        }
+        "littlekiller" => {
+            let words: Vec<&str> = words.collect();
+            return match words[..] {
+                [start, direction, sum] => {
+                    let killer = LittleKiller {
+                        start: parse_position(start)?,
+                        direction: parse_direction(direction)?,
+                        sum: parse_number(sum)?,
+                    };
+                    if !killer.starts_from_edge() {
+                        return Err(format!(
+                            "'littlekiller' {} {} does not start from the edge",
+                            start, direction
+                        ));
+                    }
+                    Ok(Constraint::LittleKiller(killer))
+                }
+                _ => Err(String::from(
+                    "'littlekiller' needs a cell, a direction and a sum",
+                )),
+            };
+        }
+        _ => {}
+    }
+
+    let cells = words.map(parse_position).collect::<Result<Vec<_>, _>>()?;
+    if cells.len() < 2 {
+        return Err(format!("'{}' needs at least two cells", name));
+    }
+
+    if let Some(kind) = parse_dot_kind(name) {
+        let (a, b) = parse_pair(name, &cells)?;
+        return Ok(Constraint::Dot(Dot {
+            kind,
+            cells: (a, b),
+        }));
+    }
+
+    let constraint = match name {
+        "thermo" => Constraint::Thermometer(Thermometer { cells }),
+        "arrow" => Constraint::Arrow(Arrow {
+            circle: cells[0],
+            arrow: cells[1..].to_vec(),
+        }),
+        "whispers" => Constraint::GermanWhispers(GermanWhispers { cells }),
+        "renban" => Constraint::Renban(Renban { cells }),
+        "palindrome" => Constraint::Palindrome(Palindrome { cells }),
+        "greater" => {
+            let (greater, smaller) = parse_pair(name, &cells)?;
+            Constraint::GreaterThan(GreaterThan { greater, smaller })
+        }
+        _ => return Err(format!("unknown constraint '{}'", name)),
+    };
+    Ok(constraint)
+}
+
+fn restriction(name: &str, cells: Vec<Position>, allowed: Allowed) -> Result<Constraint, String> {
+    if cells.is_empty() {
+        return Err(format!("'{}' needs at least one cell", name));
+    }
+    Ok(Constraint::CellRestriction(CellRestriction {
+        cells,
+        allowed,
+    }))
+}
+
+fn parse_digits(word: &str) -> Result<Vec<u8>, String> {
+    word.chars()
+        .map(|c| match c {
+            '1'..='9' => Ok(c as u8 - b'0'),
+            _ => Err(format!("invalid digits '{}'", word)),
+        })
+        .collect()
+}
+
+fn negative(kind: DotKind) -> Constraint {
+    Constraint::NegativeDots(NegativeDots { kind })
+}
+
+fn parse_dot_kind(word: &str) -> Option<DotKind> {
+    match word {
+        "white" => Some(DotKind::White),
+        "black" => Some(DotKind::Black),
+        "x" => Some(DotKind::X),
+        "v" => Some(DotKind::V),
+        _ => None,
+    }
+}
+
+fn dot_kind_name(kind: DotKind) -> &'static str {
+    match kind {
+        DotKind::White => "white",
+        DotKind::Black => "black",
+        DotKind::X => "x",
+        DotKind::V => "v",
+    }
+}
+
+fn parse_outside(side: &str, index: &str) -> Result<Outside, String> {
+    let side = match side {
+        "top" => Side::Top,
+        "bottom" => Side::Bottom,
+        "left" => Side::Left,
+        "right" => Side::Right,
+        _ => return Err(format!("invalid side '{}'", side)),
+    };
+    match index.parse::<usize>() {
+        Ok(index) if (1..=SUDOKU_SIZE).contains(&index) => Ok(Outside {
+            side,
+            index: index - 1,
+        }),
+        _ => Err(format!("invalid row or column '{}'", index)),
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+        Side::Left => "left",
+        Side::Right => "right",
+    }
+}
+
+fn parse_direction(word: &str) -> Result<Direction, String> {
+    match word {
+        "dr" => Ok(Direction::DownRight),
+        "dl" => Ok(Direction::DownLeft),
+        "ur" => Ok(Direction::UpRight),
+        "ul" => Ok(Direction::UpLeft),
+        _ => Err(format!("invalid direction '{}'", word)),
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::DownRight => "dr",
+        Direction::DownLeft => "dl",
+        Direction::UpRight => "ur",
+        Direction::UpLeft => "ul",
+    }
+}
+
+fn parse_number(word: &str) -> Result<usize, String> {
+    word.parse()
+        .map_err(|_| format!("invalid number '{}'", word))
+}
+
+fn parse_pair(name: &str, cells: &[Position]) -> Result<(Position, Position), String> {
+    match cells {
+        [a, b] if a.is_adjacent(*b) => Ok((*a, *b)),
+        _ => Err(format!("'{}' needs two adjacent cells", name)),
+    }
+}
+
+/// Parses a cell written as `r<row>c<column>`, for example `r1c9`.
+fn parse_position(word: &str) -> Result<Position, String> {
+    let invalid = || format!("invalid cell '{}', expected r<row>c<column>", word);
+
+    let rest = word.strip_prefix('r').ok_or_else(invalid)?;
+    let column_start = rest.find('c').ok_or_else(invalid)?;
+    let row: usize = rest[..column_start].parse().map_err(|_| invalid())?;
+    let column: usize = rest[column_start + 1..].parse().map_err(|_| invalid())?;
+
+    if !(1..=SUDOKU_SIZE).contains(&row) || !(1..=SUDOKU_SIZE).contains(&column) {
+        return Err(invalid());
+    }
+
+    Ok(Position {
+        x: column - 1,
+        y: row - 1,
+    })
+}
+
+/// Writes the puzzle in the puzzle file format.
+pub fn write(puzzle: &Puzzle) -> String {
+    let mut output = String::new();
+    for y in 0..SUDOKU_SIZE {
+        for x in 0..SUDOKU_SIZE {
+            output.push(match puzzle.sudoku.value(Position { x, y }) {
+                Some(value) => (b'0' + value) as char,
+                None => '.',
+            });
+        }
+        output.push('\n');
+    }
+    for constraint in puzzle.constraints.iter() {
+        output.push_str(&constraint.to_string());
+        output.push('\n');
+    }
+    output
+}
+
+impl Display for Position {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "r{}c{}", self.y + 1, self.x + 1)
+    }
+}
+
+/// Parses a position in the `r<row>c<column>` form written by `Display`.
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_position(s)
+    }
+}
+
+/// Writes the constraint as a line of the puzzle file format.
+impl Display for Constraint {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, cells) = match self {
+            Constraint::Thermometer(thermometer) => ("thermo", thermometer.cells.clone()),
+            Constraint::Arrow(arrow) => {
+                let mut cells = vec![arrow.circle];
+                cells.extend(arrow.arrow.iter());
+                ("arrow", cells)
+            }
+            Constraint::GermanWhispers(whispers) => ("whispers", whispers.cells.clone()),
+            Constraint::Renban(renban) => ("renban", renban.cells.clone()),
+            Constraint::Palindrome(palindrome) => ("palindrome", palindrome.cells.clone()),
+            Constraint::Dot(dot) => (dot_kind_name(dot.kind), vec![dot.cells.0, dot.cells.1]),
+            Constraint::GreaterThan(greater) => ("greater", vec![greater.greater, greater.smaller]),
+            Constraint::NegativeDots(negative) => {
+                return write!(fmt, "negative {}", dot_kind_name(negative.kind))
+            }
+            Constraint::Sandwich(sandwich) => {
+                return write_outside(fmt, "sandwich", sandwich.location, sandwich.sum)
+            }
+            Constraint::Skyscraper(skyscraper) => {
+                return write_outside(fmt, "skyscraper", skyscraper.location, skyscraper.count)
+            }
+            Constraint::XSum(x_sum) => {
+                return write_outside(fmt, "xsum", x_sum.location, x_sum.sum)
+            }
+            Constraint::CellRestriction(restriction) => match &restriction.allowed {
+                Allowed::Even => ("even", restriction.cells.clone()),
+                Allowed::Odd => ("odd", restriction.cells.clone()),
+                Allowed::Digits(digits) => {
+                    write!(fmt, "digits ")?;
+                    for digit in digits {
+                        write!(fmt, "{}", digit)?;
+                    }
+                    for cell in restriction.cells.iter() {
+                        write!(fmt, " {}", cell)?;
+                    }
+                    return Ok(());
+                }
+            },
+            Constraint::LittleKiller(killer) => {
+                return write!(
+                    fmt,
+                    "littlekiller {} {} {}",
+                    killer.start,
+                    direction_name(killer.direction),
+                    killer.sum
+                )
+            }
+        };
+
+        write!(fmt, "{}", name)?;
+        for cell in cells {
+            write!(fmt, " {}", cell)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_outside(
+    fmt: &mut fmt::Formatter<'_>,
+    name: &str,
+    location: Outside,
+    value: usize,
+) -> fmt::Result {
+    write!(
+        fmt,
+        "{} {} {} {}",
+        name,
+        side_name(location.side),
+        location.index + 1,
+        value
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let puzzle: Puzzle = "
+            # comment
+            ..3...6..
+            .4.7.8.3.
+            1........
+            .........
+            .........
+            .........
+            .........
+            .........
+            ........4
+            thermo r1c1 r1c2
+            arrow r9c1 r8c1 r7c1
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(Some(3), Position { x: 2, y: 0 }.value(&puzzle.sudoku.cells));
+        assert_eq!(Some(1), Position { x: 0, y: 2 }.value(&puzzle.sudoku.cells));
+        assert_eq!(None, Position { x: 0, y: 0 }.value(&puzzle.sudoku.cells));
+        assert_eq!(
+            vec![
+                Constraint::Thermometer(Thermometer {
+                    cells: vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }],
+                }),
+                Constraint::Arrow(Arrow {
+                    circle: Position { x: 0, y: 8 },
+                    arrow: vec![Position { x: 0, y: 7 }, Position { x: 0, y: 6 }],
+                }),
+            ],
+            puzzle.constraints
+        );
+    }
+
+    #[test]
+    fn test_constraint_round_trip() {
+        let lines = "thermo r1c1 r1c2 r1c3\narrow r5c5 r4c5\nwhite r1c1 r1c2\ngreater r2c1 r1c1\nnegative x\nsandwich bottom 9 0\nlittlekiller r9c1 ur 45\neven r1c1 r1c2\ndigits 19 r5c5";
+        let puzzle: Puzzle = format!("{}\n{}", ".".repeat(81), lines).parse().unwrap();
+        let written: Vec<String> = puzzle.constraints.iter().map(|c| c.to_string()).collect();
+        assert_eq!(lines, written.join("\n"));
+    }
+
+    #[test]
+    fn test_write() {
+        let text = format!(
+            "12{}\n{}renban r1c1 r1c2\n",
+            ".".repeat(7),
+            ".........\n".repeat(8)
+        );
+        let puzzle: Puzzle = text.parse().unwrap();
+        assert_eq!(text, write(&puzzle));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("123".parse::<Puzzle>().is_err());
+        assert!(format!("{}\nthermo r1c1 r0c2", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_err());
+        assert!(format!("{}\nwhite r1c1 r1c3", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_err());
+        assert!(format!("{}\nfoo r1c1 r1c2", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_err());
+        assert!(format!("{}\nlittlekiller r5c5 dr 10", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_err());
+        assert!(format!("{}\nlittlekiller r1c9 dr 10", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_ok());
+        assert!(format!("{}\nlittlekiller r9c2 dr 10", ".".repeat(81))
+            .parse::<Puzzle>()
+            .is_err());
+    }
+}
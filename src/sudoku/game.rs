@@ -0,0 +1,429 @@
+//! A game session: the givens are locked, the digits and pencil marks of the
+//! player are changed by moves, and every move is kept in a history tree that
+//! supports undo, redo and going back to an earlier point to try another way.
+
+use super::candidates::CandidateGrid;
+use super::format;
+use super::variant::Puzzle;
+use super::{Origin, Position, Sudoku};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Move {
+    /// Writes a digit into a cell. Written as `r1c2=5`.
+    Place(Position, u8),
+    /// Removes the digit of a cell. Written as `r1c2=.`.
+    Erase(Position),
+    /// Adds the pencil mark if it is missing, removes it otherwise. Written as
+    /// `r1c2:5`.
+    ToggleNote(Position, u8),
+    /// Removes all pencil marks of a cell. Written as `r1c2:.`.
+    ClearNotes(Position),
+}
+
+impl Move {
+    pub fn position(self) -> Position {
+        match self {
+            Move::Place(position, _)
+            | Move::Erase(position)
+            | Move::ToggleNote(position, _)
+            | Move::ClearNotes(position) => position,
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Place(position, value) => write!(fmt, "{}={}", position, value),
+            Move::Erase(position) => write!(fmt, "{}=.", position),
+            Move::ToggleNote(position, value) => write!(fmt, "{}:{}", position, value),
+            Move::ClearNotes(position) => write!(fmt, "{}:.", position),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid move '{}'", s);
+        let split = s.find(['=', ':']).ok_or_else(invalid)?;
+        let position = s[..split].parse()?;
+        let value = match &s[split + 1..] {
+            "." => None,
+            digit => match digit.parse() {
+                Ok(value @ 1..=9) => Some(value),
+                _ => return Err(invalid()),
+            },
+        };
+        Ok(match (&s[split..split + 1], value) {
+            ("=", Some(value)) => Move::Place(position, value),
+            ("=", None) => Move::Erase(position),
+            (_, Some(value)) => Move::ToggleNote(position, value),
+            (_, None) => Move::ClearNotes(position),
+        })
+    }
+}
+
+/// A point in the history of a game to come back to with `Game::revert`. It
+/// stays valid in the clones of the game and in the game saved and loaded,
+/// which keep the history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// The digit and pencil marks of one cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CellState {
+    value: Option<u8>,
+    notes: Vec<u8>,
+}
+
+/// A move with the state of its cell before and after it, so that it can be
+/// undone and redone.
+#[derive(Debug, Clone)]
+struct Step {
+    action: Move,
+    before: CellState,
+    after: CellState,
+}
+
+/// A node of the history tree. The root is the start of the game and has no
+/// step.
+#[derive(Debug, Clone)]
+struct Node {
+    parent: Option<usize>,
+    step: Option<Step>,
+    /// The child visited last, which `redo` goes back to.
+    redo: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "History", try_from = "History")]
+pub struct Game {
+    givens: Puzzle,
+    current: Puzzle,
+    notes: CandidateGrid,
+    nodes: Vec<Node>,
+    node: usize,
+}
+
+/// The serialised form of a game: the puzzle and the history tree.
+#[derive(Serialize, Deserialize)]
+struct History {
+    /// The givens and constraints in the puzzle file format.
+    puzzle: String,
+    /// The moves with the index of their parent, 0 being the start of the
+    /// game and the moves numbered from 1.
+    moves: Vec<(usize, String)>,
+    /// The index of the current point in the history.
+    current: usize,
+}
+
+impl Game {
+    pub fn new(puzzle: Puzzle) -> Game {
+        Game {
+            current: puzzle.clone(),
+            givens: puzzle,
+            notes: CandidateGrid::empty(),
+            nodes: vec![Node {
+                parent: None,
+                step: None,
+                redo: None,
+            }],
+            node: 0,
+        }
+    }
+
+    /// The puzzle with only the givens.
+    pub fn givens(&self) -> &Puzzle {
+        &self.givens
+    }
+
+    /// The puzzle with the givens and the digits of the player.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.current
+    }
+
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.current.sudoku
+    }
+
+    pub fn is_given(&self, position: Position) -> bool {
+        self.givens.sudoku.value(position).is_some()
+    }
+
+    /// The pencil marks of the player.
+    pub fn notes(&self) -> &CandidateGrid {
+        &self.notes
+    }
+
+    /// Plays a move. Moves that change nothing are not recorded. If moves were
+    /// undone, they stay in the history as another branch and `redo` follows
+    /// the new move.
+    pub fn play(&mut self, action: Move) -> Result<(), String> {
+        let position = action.position();
+        if self.is_given(position) {
+            return Err(format!("{} is given", position));
+        }
+        let before = self.cell(position);
+        let mut after = before.clone();
+        match action {
+            Move::Place(_, value) if (1..=9).contains(&value) => after.value = Some(value),
+            Move::Erase(_) => after.value = None,
+            Move::ToggleNote(_, value) if (1..=9).contains(&value) => {
+                match after.notes.iter().position(|note| *note == value) {
+                    Some(i) => {
+                        after.notes.remove(i);
+                    }
+                    None => {
+                        after.notes.push(value);
+                        after.notes.sort_unstable();
+                    }
+                }
+            }
+            Move::ClearNotes(_) => after.notes.clear(),
+            _ => return Err(format!("invalid move {}", action)),
+        }
+        if after == before {
+            return Ok(());
+        }
+
+        self.set_cell(position, &after);
+        self.nodes.push(Node {
+            parent: Some(self.node),
+            step: Some(Step {
+                action,
+                before,
+                after,
+            }),
+            redo: None,
+        });
+        let node = self.nodes.len() - 1;
+        self.nodes[self.node].redo = Some(node);
+        self.node = node;
+        Ok(())
+    }
+
+    /// Takes back the last move. Returns `false` at the start of the game.
+    pub fn undo(&mut self) -> bool {
+        let parent = match self.nodes[self.node].parent {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let step = self.nodes[self.node].step.clone().unwrap();
+        self.set_cell(step.action.position(), &step.before);
+        self.nodes[parent].redo = Some(self.node);
+        self.node = parent;
+        true
+    }
+
+    /// Plays the last undone move again. Returns `false` if there is none.
+    pub fn redo(&mut self) -> bool {
+        match self.nodes[self.node].redo {
+            Some(child) => {
+                self.enter(child);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.nodes[self.node].parent.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.nodes[self.node].redo.is_some()
+    }
+
+    /// The current point in the history.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.node)
+    }
+
+    /// Goes back (or forward) to a checkpoint. The moves played since then
+    /// stay in the history and can be reached again with `redo` or another
+    /// checkpoint. Fails without changing anything if the history of the
+    /// game does not have the checkpoint, for example if it is from another
+    /// game with a longer history.
+    pub fn revert(&mut self, checkpoint: Checkpoint) -> Result<(), String> {
+        if checkpoint.0 >= self.nodes.len() {
+            return Err(format!(
+                "the game does not have checkpoint {}",
+                checkpoint.0
+            ));
+        }
+        let mut path = Vec::new();
+        let mut node = Some(checkpoint.0);
+        while let Some(index) = node {
+            path.push(index);
+            node = self.nodes[index].parent;
+        }
+        while !path.contains(&self.node) {
+            self.undo();
+        }
+        let start = path.iter().position(|node| *node == self.node).unwrap();
+        for node in path[..start].iter().rev() {
+            self.enter(*node);
+        }
+        Ok(())
+    }
+
+    /// The moves from the start of the game to the current point, to replay
+    /// the game with `play`.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut node = self.node;
+        while let Some(parent) = self.nodes[node].parent {
+            moves.push(self.nodes[node].step.as_ref().unwrap().action);
+            node = parent;
+        }
+        moves.reverse();
+        moves
+    }
+
+    fn cell(&self, position: Position) -> CellState {
+        CellState {
+            value: self.current.sudoku.value(position),
+            notes: self.notes.get(position),
+        }
+    }
+
+    fn set_cell(&mut self, position: Position, cell: &CellState) {
+        self.current
+            .sudoku
+            .set_cell(position, cell.value, Origin::User);
+        self.notes.set(position, &cell.notes);
+    }
+
+    /// Moves to a child of the current node.
+    fn enter(&mut self, child: usize) {
+        let step = self.nodes[child].step.clone().unwrap();
+        self.set_cell(step.action.position(), &step.after);
+        self.nodes[self.node].redo = Some(child);
+        self.node = child;
+    }
+}
+
+impl From<Game> for History {
+    fn from(game: Game) -> History {
+        History {
+            puzzle: format::write(&game.givens),
+            moves: game.nodes[1..]
+                .iter()
+                .map(|node| {
+                    let step = node.step.as_ref().unwrap();
+                    (node.parent.unwrap(), step.action.to_string())
+                })
+                .collect(),
+            current: game.node,
+        }
+    }
+}
+
+impl TryFrom<History> for Game {
+    type Error = String;
+
+    fn try_from(history: History) -> Result<Game, String> {
+        let mut game = Game::new(history.puzzle.parse()?);
+        for (parent, action) in history.moves.iter() {
+            game.revert(Checkpoint(*parent))
+                .map_err(|_| format!("invalid parent {} of move {}", parent, action))?;
+            let count = game.nodes.len();
+            game.play(action.parse()?)?;
+            if game.nodes.len() == count {
+                return Err(format!("move {} changes nothing", action));
+            }
+        }
+        game.revert(Checkpoint(history.current))
+            .map_err(|_| format!("invalid current move {}", history.current))?;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n\
+                          7...2...6\n.6....28.\n...419..5\n....8..79\n";
+
+    fn at(row: usize, column: usize) -> Position {
+        Position {
+            x: column - 1,
+            y: row - 1,
+        }
+    }
+
+    #[test]
+    fn test_move_format() {
+        for text in ["r1c3=4", "r1c3=.", "r9c9:7", "r2c1:."].iter() {
+            let action: Move = text.parse().unwrap();
+            assert_eq!(action.to_string(), *text);
+        }
+        assert_eq!("r1c3=4".parse(), Ok(Move::Place(at(1, 3), 4)));
+        assert!("r1c3=0".parse::<Move>().is_err());
+        assert!("r1c3".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut game = Game::new(PUZZLE.parse().unwrap());
+        assert!(game.play(Move::Place(at(1, 1), 1)).is_err());
+        game.play(Move::Place(at(1, 3), 4)).unwrap();
+        game.play(Move::ToggleNote(at(1, 4), 6)).unwrap();
+        game.play(Move::ToggleNote(at(1, 4), 2)).unwrap();
+        assert_eq!(game.notes().get(at(1, 4)), vec![2, 6]);
+
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(!game.undo());
+        assert_eq!(game.sudoku().value(at(1, 3)), None);
+        assert_eq!(game.notes().count(at(1, 4)), 0);
+
+        assert!(game.redo());
+        assert!(game.redo());
+        assert!(game.redo());
+        assert!(!game.redo());
+        assert_eq!(game.sudoku().value(at(1, 3)), Some(4));
+        assert_eq!(game.notes().get(at(1, 4)), vec![2, 6]);
+        assert_eq!(game.moves().len(), 3);
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut game = Game::new(PUZZLE.parse().unwrap());
+        game.play(Move::Place(at(1, 3), 4)).unwrap();
+        let checkpoint = game.checkpoint();
+
+        game.play(Move::Place(at(1, 4), 6)).unwrap();
+        game.play(Move::Place(at(1, 6), 8)).unwrap();
+        let tried = game.checkpoint();
+        game.revert(checkpoint).unwrap();
+        assert_eq!(game.sudoku().value(at(1, 4)), None);
+        assert_eq!(game.moves(), vec![Move::Place(at(1, 3), 4)]);
+
+        game.play(Move::Place(at(1, 4), 2)).unwrap();
+        assert!(!game.can_redo());
+        game.revert(tried).unwrap();
+        assert_eq!(game.sudoku().value(at(1, 4)), Some(6));
+        assert_eq!(game.sudoku().value(at(1, 6)), Some(8));
+
+        let json = serde_json::to_string(&game).unwrap();
+        let mut loaded: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.puzzle(), game.puzzle());
+        assert_eq!(loaded.moves(), game.moves());
+        loaded.revert(checkpoint).unwrap();
+        loaded.redo();
+        assert_eq!(loaded.sudoku().value(at(1, 4)), Some(6));
+
+        let mut other = Game::new(PUZZLE.parse().unwrap());
+        assert!(other.revert(tried).is_err());
+        assert!(other.moves().is_empty());
+    }
+}
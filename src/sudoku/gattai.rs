@@ -0,0 +1,632 @@
+//! Gattai puzzles are several sudoku grids overlapping each other, for example
+//! Samurai where five grids share their corner boxes. The grids are placed on
+//! a larger layout and a cell that belongs to more than one grid is a single
+//! cell in the layout, so a digit placed to it counts in every grid.
+//!
+//! `Display` draws the layout as text and `render::draw_gattai` draws it for
+//! the SVG, PNG and PDF output.
+
+use super::validate::House;
+use super::{Cell, Cells, Origin, Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+
+#[derive(Debug, Clone)]
+pub struct Gattai {
+    width: usize,
+    height: usize,
+    /// The top left corner of each grid in the layout.
+    grids: Vec<Position>,
+    /// The digits and their origins in row-major order. Cells outside the
+    /// grids are always `None`.
+    cells: Vec<Option<Cell>>,
+    /// The cells that see each cell in any of the grids.
+    peers: Vec<Vec<usize>>,
+    /// The rows, columns and boxes of all the grids. A box shared by two
+    /// grids is listed once.
+    houses: Vec<Vec<usize>>,
+    /// The indices of the houses of each cell in `houses`.
+    cell_houses: Vec<Vec<usize>>,
+}
+
+impl Gattai {
+    /// Creates an empty layout of grids whose top left corners are at `grids`.
+    /// The grids must be aligned to boxes so that the overlapping parts are
+    /// whole boxes.
+    pub fn new(grids: Vec<Position>) -> Result<Gattai, String> {
+        if grids.is_empty() {
+            return Err(String::from("no grids"));
+        }
+        if let Some(grid) = grids
+            .iter()
+            .find(|grid| grid.x % BOX_SIZE != 0 || grid.y % BOX_SIZE != 0)
+        {
+            return Err(format!("grid at {:?} is not aligned to boxes", grid));
+        }
+
+        let width = grids.iter().map(|grid| grid.x).max().unwrap() + SUDOKU_SIZE;
+        let height = grids.iter().map(|grid| grid.y).max().unwrap() + SUDOKU_SIZE;
+
+        let mut peers = vec![Vec::new(); width * height];
+        for grid in grids.iter() {
+            for a in grid_cells(*grid) {
+                for b in grid_cells(*grid) {
+                    let same_box =
+                        a.x / BOX_SIZE == b.x / BOX_SIZE && a.y / BOX_SIZE == b.y / BOX_SIZE;
+                    if a != b && (a.x == b.x || a.y == b.y || same_box) {
+                        peers[a.y * width + a.x].push(b.y * width + b.x);
+                    }
+                }
+            }
+        }
+        for cell_peers in peers.iter_mut() {
+            cell_peers.sort_unstable();
+            cell_peers.dedup();
+        }
+
+        let mut houses: Vec<Vec<usize>> = grids
+            .iter()
+            .flat_map(|grid| {
+                House::all().map(move |house| {
+                    let mut cells: Vec<usize> = house
+                        .cells()
+                        .map(|cell| (grid.y + cell.y) * width + grid.x + cell.x)
+                        .collect();
+                    cells.sort_unstable();
+                    cells
+                })
+            })
+            .collect();
+        houses.sort_unstable();
+        houses.dedup();
+        let mut cell_houses = vec![Vec::new(); width * height];
+        for (house, cells) in houses.iter().enumerate() {
+            for cell in cells.iter() {
+                cell_houses[*cell].push(house);
+            }
+        }
+
+        Ok(Gattai {
+            width,
+            height,
+            grids,
+            cells: vec![None; width * height],
+            peers,
+            houses,
+            cell_houses,
+        })
+    }
+
+    /// Five grids where the middle one shares a box with each corner grid.
+    pub fn samurai() -> Gattai {
+        Gattai::new(vec![
+            Position { x: 0, y: 0 },
+            Position { x: 12, y: 0 },
+            Position { x: 6, y: 6 },
+            Position { x: 0, y: 12 },
+            Position { x: 12, y: 12 },
+        ])
+        .unwrap()
+    }
+
+    /// Two grids sharing one box.
+    pub fn twodoku() -> Gattai {
+        Gattai::new(vec![Position { x: 0, y: 0 }, Position { x: 6, y: 6 }]).unwrap()
+    }
+
+    /// Four grids overlapping in a 12x12 square.
+    pub fn butterfly() -> Gattai {
+        Gattai::new(vec![
+            Position { x: 0, y: 0 },
+            Position { x: 3, y: 0 },
+            Position { x: 0, y: 3 },
+            Position { x: 3, y: 3 },
+        ])
+        .unwrap()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if `position` is a cell of any of the grids.
+    pub fn contains(&self, position: Position) -> bool {
+        self.grids.iter().any(|grid| {
+            (grid.x..grid.x + SUDOKU_SIZE).contains(&position.x)
+                && (grid.y..grid.y + SUDOKU_SIZE).contains(&position.y)
+        })
+    }
+
+    pub fn get(&self, position: Position) -> Option<u8> {
+        self.cell(position).map(|cell| cell.value)
+    }
+
+    /// Where the digit of a cell comes from, `None` for an empty cell.
+    pub fn origin(&self, position: Position) -> Option<Origin> {
+        self.cell(position).map(|cell| cell.origin)
+    }
+
+    fn cell(&self, position: Position) -> Option<Cell> {
+        if position.x < self.width && position.y < self.height {
+            self.cells[position.y * self.width + position.x]
+        } else {
+            None
+        }
+    }
+
+    fn value_of(&self, index: usize) -> Option<u8> {
+        self.cells[index].map(|cell| cell.value)
+    }
+
+    /// Writes a given digit into a cell, or empties it with `None`.
+    pub fn set(&mut self, position: Position, value: Option<u8>) -> Result<(), String> {
+        if !self.contains(position) {
+            return Err(format!("{:?} is not in any grid", position));
+        }
+        if value.is_some_and(|value| !(1..=SUDOKU_SIZE as u8).contains(&value)) {
+            return Err(format!("invalid digit {:?}", value));
+        }
+        self.cells[position.y * self.width + position.x] = value.map(|value| Cell {
+            value,
+            origin: Origin::Given,
+        });
+        Ok(())
+    }
+
+    /// Reads the digits from text with one line per row of the layout. A
+    /// digit is a given and '.' or '0' is an empty cell. Characters outside
+    /// the grids are ignored so the gaps can be filled with spaces, and the
+    /// cells past the end of a short row are empty.
+    pub fn load(&mut self, text: &str) -> Result<(), String> {
+        let rows: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if rows.len() != self.height {
+            return Err(format!(
+                "expected {} rows, found {}",
+                self.height,
+                rows.len()
+            ));
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            let mut chars = row.chars();
+            for x in 0..self.width {
+                let c = chars.next();
+                let position = Position { x, y };
+                if !self.contains(position) {
+                    continue;
+                }
+                let value = match c {
+                    None | Some('.') | Some('0') => None,
+                    Some(c @ '1'..='9') => Some(c as u8 - b'0'),
+                    Some(c) => {
+                        return Err(format!("unexpected character '{}' at {:?}", c, position))
+                    }
+                };
+                self.set(position, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The grid number `index` as a normal sudoku.
+    pub fn grid(&self, index: usize) -> Sudoku {
+        let corner = self.grids[index];
+        let mut cells: Cells = [[None; SUDOKU_SIZE]; SUDOKU_SIZE];
+        for (x, column) in cells.iter_mut().enumerate() {
+            for (y, cell) in column.iter_mut().enumerate() {
+                *cell = self.cell(Position {
+                    x: corner.x + x,
+                    y: corner.y + y,
+                });
+            }
+        }
+        Sudoku { cells }
+    }
+
+    pub fn grid_count(&self) -> usize {
+        self.grids.len()
+    }
+
+    /// Solves all the grids at once. The digits it places are `Origin::Solver`.
+    /// Returns `false` if there is no solution, for example because two
+    /// givens that see each other have the same digit, in which case the
+    /// digits are left as they were.
+    pub fn solve(&mut self) -> bool {
+        if self.has_conflicts() {
+            return false;
+        }
+        let before = self.cells.clone();
+        let mut masks = self.masks();
+        let mut singles = Vec::new();
+        if self.find_singles(&masks, &mut singles) && self.solve_internal(&mut masks, singles) {
+            return true;
+        }
+        self.cells = before;
+        false
+    }
+
+    /// `true` if a digit is in a cell that sees the same digit in any grid.
+    fn has_conflicts(&self) -> bool {
+        (0..self.cells.len()).any(|index| {
+            let value = self.value_of(index);
+            value.is_some()
+                && self.peers[index]
+                    .iter()
+                    .any(|peer| self.value_of(*peer) == value)
+        })
+    }
+
+    /// Places the `singles` and the naked and hidden singles they lead to
+    /// until there are none left, then guesses the empty cell with the fewest
+    /// candidates. A digit placed to a box shared by two grids takes the
+    /// candidates of its peers in both of them.
+    fn solve_internal(&mut self, masks: &mut Vec<u16>, mut singles: Vec<(usize, u8)>) -> bool {
+        while let Some((index, value)) = singles.pop() {
+            if masks[index] & 1 << (value - 1) == 0 {
+                // A cell can be both a naked and a hidden single.
+                if self.value_of(index) == Some(value) {
+                    continue;
+                }
+                return false;
+            }
+            if !self.place(masks, &mut singles, index, value) {
+                return false;
+            }
+        }
+
+        // Only the empty cells of the grids have candidates.
+        let index = match (0..masks.len())
+            .filter(|index| masks[*index] != 0)
+            .min_by_key(|index| masks[*index].count_ones())
+        {
+            Some(index) => index,
+            None => return true,
+        };
+
+        for candidate in digits(masks[index]) {
+            let before = (self.cells.clone(), masks.clone());
+            let mut singles = Vec::new();
+            if self.place(masks, &mut singles, index, candidate)
+                && self.solve_internal(masks, singles)
+            {
+                return true;
+            }
+            self.cells = before.0;
+            *masks = before.1;
+        }
+        false
+    }
+
+    /// Bit `d - 1` is set when `d` is a candidate of the cell, 0 for the
+    /// filled cells and the cells outside the grids.
+    fn masks(&self) -> Vec<u16> {
+        (0..self.cells.len())
+            .map(|index| {
+                let position = Position {
+                    x: index % self.width,
+                    y: index / self.width,
+                };
+                if self.contains(position) && self.cells[index].is_none() {
+                    self.candidates_of(index)
+                        .iter()
+                        .fold(0, |mask, value| mask | 1 << (value - 1))
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    /// Pushes every naked and hidden single to `singles`. Returns `false` if
+    /// an empty cell has no candidate or a digit has no place in a house.
+    fn find_singles(&self, masks: &[u16], singles: &mut Vec<(usize, u8)>) -> bool {
+        for (index, mask) in masks.iter().enumerate() {
+            let position = Position {
+                x: index % self.width,
+                y: index / self.width,
+            };
+            if !self.contains(position) || self.cells[index].is_some() {
+                continue;
+            }
+            match mask.count_ones() {
+                0 => return false,
+                1 => singles.push((index, digits(*mask).next().unwrap())),
+                _ => {}
+            }
+        }
+        (0..self.houses.len())
+            .all(|house| self.find_hidden_singles(masks, singles, house, (1 << SUDOKU_SIZE) - 1))
+    }
+
+    /// Pushes the digits of `values` that have only one place left in `house`
+    /// to `singles`. Returns `false` if one of them has no place.
+    fn find_hidden_singles(
+        &self,
+        masks: &[u16],
+        singles: &mut Vec<(usize, u8)>,
+        house: usize,
+        values: u16,
+    ) -> bool {
+        let cells = &self.houses[house];
+        for value in digits(values) {
+            if cells.iter().any(|cell| self.value_of(*cell) == Some(value)) {
+                continue;
+            }
+            let mut places = cells
+                .iter()
+                .filter(|cell| masks[**cell] & 1 << (value - 1) != 0);
+            match (places.next(), places.next()) {
+                (None, _) => return false,
+                (Some(cell), None) => singles.push((*cell, value)),
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Places `value` to the cell and takes it from the candidates of the
+    /// peers. The singles this leaves are pushed to `singles`. Returns
+    /// `false` if a peer is left without a candidate or a digit without a
+    /// place in a house.
+    fn place(
+        &mut self,
+        masks: &mut [u16],
+        singles: &mut Vec<(usize, u8)>,
+        index: usize,
+        value: u8,
+    ) -> bool {
+        let bit = 1 << (value - 1);
+        self.cells[index] = Some(Cell {
+            value,
+            origin: Origin::Solver,
+        });
+
+        // The digits each house lost a place for.
+        let mut lost = vec![0u16; self.houses.len()];
+        for house in self.cell_houses[index].iter() {
+            lost[*house] |= masks[index] & !bit;
+        }
+        masks[index] = 0;
+
+        for peer in self.peers[index].iter() {
+            if masks[*peer] & bit == 0 {
+                continue;
+            }
+            masks[*peer] &= !bit;
+            match masks[*peer].count_ones() {
+                0 => return false,
+                1 => singles.push((*peer, digits(masks[*peer]).next().unwrap())),
+                _ => {}
+            }
+            for house in self.cell_houses[*peer].iter() {
+                lost[*house] |= bit;
+            }
+        }
+
+        lost.iter()
+            .enumerate()
+            .filter(|(_, values)| **values != 0)
+            .all(|(house, values)| self.find_hidden_singles(masks, singles, house, *values))
+    }
+
+    /// The digits that no cell seeing `position` in any of the grids has.
+    pub fn candidates(&self, position: Position) -> Vec<u8> {
+        if !self.contains(position) || self.get(position).is_some() {
+            return Vec::new();
+        }
+        self.candidates_of(position.y * self.width + position.x)
+    }
+
+    fn candidates_of(&self, index: usize) -> Vec<u8> {
+        let mut possible = [true; SUDOKU_SIZE];
+        for peer in self.peers[index].iter() {
+            if let Some(value) = self.value_of(*peer) {
+                possible[(value - 1) as usize] = false;
+            }
+        }
+        (1..=SUDOKU_SIZE as u8)
+            .filter(|value| possible[(*value - 1) as usize])
+            .collect()
+    }
+}
+
+/// The digits whose bits are set in `mask`, smallest first.
+fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=SUDOKU_SIZE as u8).filter(move |value| mask & 1 << (value - 1) != 0)
+}
+
+fn grid_cells(corner: Position) -> impl Iterator<Item = Position> {
+    (0..SUDOKU_SIZE * SUDOKU_SIZE).map(move |i| Position {
+        x: corner.x + i % SUDOKU_SIZE,
+        y: corner.y + i / SUDOKU_SIZE,
+    })
+}
+
+/// Draws the whole layout with the box borders of every grid. Empty cells
+/// are shown as '.'.
+impl std::fmt::Display for Gattai {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let contains = |x: isize, y: isize| {
+            x >= 0
+                && y >= 0
+                && self.contains(Position {
+                    x: x as usize,
+                    y: y as usize,
+                })
+        };
+
+        for y in 0..=self.height as isize {
+            if y % BOX_SIZE as isize == 0 {
+                let mut border = String::new();
+                for x in 0..=self.width as isize {
+                    if x % BOX_SIZE as isize == 0 {
+                        if x > 0 {
+                            let above_or_below = contains(x - 1, y - 1) || contains(x - 1, y);
+                            border.push(if above_or_below { '-' } else { ' ' });
+                        }
+                        let around = contains(x - 1, y - 1)
+                            || contains(x - 1, y)
+                            || contains(x, y - 1)
+                            || contains(x, y);
+                        border.push(if around { '+' } else { ' ' });
+                    }
+                    if x < self.width as isize {
+                        let above_or_below = contains(x, y - 1) || contains(x, y);
+                        border.push_str(if above_or_below { "--" } else { "  " });
+                    }
+                }
+                writeln!(fmt, "{}", border.trim_end())?;
+            }
+            if y == self.height as isize {
+                break;
+            }
+
+            let mut row = String::new();
+            for x in 0..=self.width as isize {
+                if x % BOX_SIZE as isize == 0 {
+                    if x > 0 {
+                        row.push(' ');
+                    }
+                    let is_border = contains(x - 1, y) || contains(x, y);
+                    row.push(if is_border { '|' } else { ' ' });
+                }
+                if x < self.width as isize {
+                    row.push(' ');
+                    row.push(
+                        match self.get(Position {
+                            x: x as usize,
+                            y: y as usize,
+                        }) {
+                            Some(value) => (b'0' + value) as char,
+                            None if contains(x, y) => '.',
+                            None => ' ',
+                        },
+                    );
+                }
+            }
+            writeln!(fmt, "{}", row.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn assert_valid(sudoku: &Sudoku) {
+        for i in 0..SUDOKU_SIZE {
+            let mut row: Vec<u8> = (0..SUDOKU_SIZE)
+                .map(|x| sudoku.cells[x][i].unwrap().value)
+                .collect();
+            let mut column: Vec<u8> = (0..SUDOKU_SIZE)
+                .map(|y| sudoku.cells[i][y].unwrap().value)
+                .collect();
+            let mut square: Vec<u8> = (0..SUDOKU_SIZE)
+                .map(|j| {
+                    let x = (i % BOX_SIZE) * BOX_SIZE + j % BOX_SIZE;
+                    let y = (i / BOX_SIZE) * BOX_SIZE + j / BOX_SIZE;
+                    sudoku.cells[x][y].unwrap().value
+                })
+                .collect();
+            for house in [&mut row, &mut column, &mut square] {
+                house.sort_unstable();
+                assert_eq!((1..=9).collect::<Vec<u8>>(), *house);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_samurai() {
+        let mut samurai = Gattai::samurai();
+        samurai.set(Position { x: 7, y: 7 }, Some(5)).unwrap();
+        assert!(samurai.solve());
+        assert_eq!(Some(5), samurai.get(Position { x: 7, y: 7 }));
+        assert_eq!(Some(Origin::Given), samurai.origin(Position { x: 7, y: 7 }));
+        assert_eq!(
+            Some(Origin::Solver),
+            samurai.origin(Position { x: 8, y: 7 })
+        );
+        assert!(samurai.grid(2).is_given(1, 1));
+        assert!(!samurai.grid(2).is_given(1, 2));
+        for index in 0..samurai.grid_count() {
+            assert_valid(&samurai.grid(index));
+        }
+    }
+
+    #[test]
+    fn test_solve_conflicting_givens() {
+        // The first and the last cell of the first row of the second grid.
+        let mut twodoku = Gattai::twodoku();
+        twodoku.set(Position { x: 6, y: 6 }, Some(3)).unwrap();
+        twodoku.set(Position { x: 14, y: 6 }, Some(3)).unwrap();
+        let before = twodoku.clone();
+        assert!(!twodoku.solve());
+        assert_eq!(before.to_string(), twodoku.to_string());
+    }
+
+    #[test]
+    fn test_hidden_single_in_shared_box() {
+        // The 1s in the first grid leave the 1 of the shared box to its left
+        // column and the 1s in the second grid to its top row.
+        let mut twodoku = Gattai::twodoku();
+        for (x, y) in [(7, 1), (8, 3), (10, 7), (13, 8)] {
+            twodoku.set(Position { x, y }, Some(1)).unwrap();
+        }
+        let masks = twodoku.masks();
+        let mut singles = Vec::new();
+        assert!(twodoku.find_singles(&masks, &mut singles));
+        assert!(singles.contains(&(6 * twodoku.width() + 6, 1)));
+
+        assert!(twodoku.solve());
+        assert_eq!(Some(1), twodoku.get(Position { x: 6, y: 6 }));
+    }
+
+    #[test]
+    fn test_load_short_rows() {
+        let mut twodoku = Gattai::twodoku();
+        twodoku.set(Position { x: 2, y: 0 }, Some(5)).unwrap();
+        let mut rows = vec![String::from("1"); twodoku.height()];
+        rows[0] = String::from("12");
+        twodoku.load(&rows.join("\n")).unwrap();
+        assert_eq!(Some(2), twodoku.get(Position { x: 1, y: 0 }));
+        assert_eq!(None, twodoku.get(Position { x: 2, y: 0 }));
+    }
+
+    #[test]
+    fn test_load_and_display_twodoku() {
+        let mut twodoku = Gattai::twodoku();
+        let mut text = String::new();
+        for y in 0..twodoku.height() {
+            for x in 0..twodoku.width() {
+                text.push(match (x, y) {
+                    (0, 0) | (6, 10) => '1',
+                    _ if twodoku.contains(Position { x, y }) => '.',
+                    _ => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        twodoku.load(&text).unwrap();
+        assert_eq!(Some(1), twodoku.get(Position { x: 6, y: 10 }));
+        assert_eq!(None, twodoku.get(Position { x: 1, y: 0 }));
+
+        let lines: Vec<String> = twodoku.to_string().lines().map(String::from).collect();
+        assert_eq!("+-------+-------+-------+", lines[0]);
+        assert_eq!("| 1 . . | . . . | . . . |", lines[1]);
+        assert_eq!("| . . . | . . . | . . . | . . . | . . . |", lines[9]);
+        assert_eq!("                | 1 . . | . . . | . . . |", lines[14]);
+        assert_eq!("                +-------+-------+-------+", lines[20]);
+    }
+
+    #[test]
+    fn test_unaligned_grid() {
+        assert!(Gattai::new(vec![Position { x: 1, y: 0 }]).is_err());
+    }
+}
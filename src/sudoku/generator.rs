@@ -0,0 +1,154 @@
+//! Generation of new puzzles with a unique solution.
+
+use super::rating::{self, Difficulty};
+use super::symmetry::Symmetry;
+use super::variant::Puzzle;
+use super::{solver, Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How many times to start over from a new solution before giving up on
+/// reaching the difficulty exactly.
+const ATTEMPTS: usize = 10;
+
+/// Generates a puzzle of the given difficulty.
+pub fn generate(difficulty: Difficulty) -> Sudoku {
+    generate_with(&mut rand::thread_rng(), difficulty)
+}
+
+/// Like `generate` but takes the random number generator, so that a seeded
+/// generator gives the same puzzle every time. If no attempt reaches the
+/// difficulty exactly, returns the closest puzzle found.
+pub fn generate_with<R: Rng>(rng: &mut R, difficulty: Difficulty) -> Sudoku {
+    generate_symmetric_with(rng, difficulty, Symmetry::None)
+}
+
+/// Generates a puzzle whose clues follow `symmetry`.
+pub fn generate_symmetric(difficulty: Difficulty, symmetry: Symmetry) -> Sudoku {
+    generate_symmetric_with(&mut rand::thread_rng(), difficulty, symmetry)
+}
+
+/// Like `generate_with` but the clues follow `symmetry`. The clues are
+/// removed in symmetric groups, so a symmetric puzzle may need more clues
+/// than one without symmetry.
+pub fn generate_symmetric_with<R: Rng>(
+    rng: &mut R,
+    difficulty: Difficulty,
+    symmetry: Symmetry,
+) -> Sudoku {
+    let mut closest: Option<(Sudoku, Difficulty)> = None;
+    for _ in 0..ATTEMPTS {
+        let (sudoku, rated) = attempt(rng, difficulty, symmetry);
+        if rated == difficulty {
+            return sudoku;
+        }
+        let distance = |rated: Difficulty| (rated.level() as i32 - difficulty.level() as i32).abs();
+        if closest.is_none_or(|(_, closest)| distance(rated) < distance(closest)) {
+            closest = Some((sudoku, rated));
+        }
+    }
+    closest.unwrap().0
+}
+
+/// Removes the clues of a random solution in random order, each together
+/// with its symmetric group, as long as the solution stays unique and the
+/// puzzle is not harder than wanted.
+fn attempt<R: Rng>(
+    rng: &mut R,
+    difficulty: Difficulty,
+    symmetry: Symmetry,
+) -> (Sudoku, Difficulty) {
+    let mut puzzle = Puzzle {
+        sudoku: random_solution(rng),
+        constraints: Vec::new(),
+    };
+    let mut positions: Vec<Position> = (0..SUDOKU_SIZE * SUDOKU_SIZE)
+        .map(|i| Position {
+            x: i % SUDOKU_SIZE,
+            y: i / SUDOKU_SIZE,
+        })
+        .collect();
+    positions.shuffle(rng);
+
+    let mut tried = Vec::new();
+    for position in positions {
+        if tried.contains(&position) {
+            continue;
+        }
+        let group = symmetry.group(position);
+        let values: Vec<Option<u8>> = group
+            .iter()
+            .map(|position| puzzle.sudoku.value(*position))
+            .collect();
+        for position in group.iter() {
+            puzzle.sudoku.set_value(*position, None);
+        }
+        let keep = solver::count_solutions(&puzzle, 2) == 1
+            && (difficulty == Difficulty::Hard || rated(&puzzle) <= difficulty);
+        if !keep {
+            for (position, value) in group.iter().zip(values) {
+                puzzle.sudoku.set_value(*position, value);
+            }
+        }
+        tried.extend(group);
+    }
+    let rated = rated(&puzzle);
+    (puzzle.sudoku, rated)
+}
+
+fn rated(puzzle: &Puzzle) -> Difficulty {
+    rating::rate(puzzle).map_or(Difficulty::Hard, |rating| rating.difficulty)
+}
+
+/// A random full grid. The boxes on the diagonal do not share rows or columns
+/// so they can be filled with any digits before the solver fills the rest.
+fn random_solution<R: Rng>(rng: &mut R) -> Sudoku {
+    let mut sudoku = Sudoku::empty();
+    for b in 0..BOX_SIZE {
+        let mut digits: Vec<u8> = (1..=SUDOKU_SIZE as u8).collect();
+        digits.shuffle(rng);
+        for (i, digit) in digits.into_iter().enumerate() {
+            let position = Position {
+                x: b * BOX_SIZE + i % BOX_SIZE,
+                y: b * BOX_SIZE + i / BOX_SIZE,
+            };
+            sudoku.set_value(position, Some(digit));
+        }
+    }
+    solver::solve(&mut sudoku);
+    sudoku
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_generate() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium].iter() {
+            let puzzle = Puzzle {
+                sudoku: generate_with(&mut rng, *difficulty),
+                constraints: Vec::new(),
+            };
+            assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+            assert_eq!(rating::rate(&puzzle).unwrap().difficulty, *difficulty);
+        }
+    }
+
+    #[test]
+    fn test_generate_symmetric() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for symmetry in [Symmetry::Rotational, Symmetry::Diagonal].iter() {
+            let sudoku = generate_symmetric_with(&mut rng, Difficulty::Medium, *symmetry);
+            let puzzle = Puzzle {
+                sudoku,
+                constraints: Vec::new(),
+            };
+            assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+            assert!(symmetry.holds(&sudoku));
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Hints for the next digit to place.
+
+use super::candidates::CandidateGrid;
+use super::validate::House;
+use super::variant::Puzzle;
+use super::{solver, Position, SUDOKU_SIZE};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Technique {
+    /// The cell has only one candidate.
+    NakedSingle,
+    /// The digit has only one place in a row, column or box.
+    HiddenSingle,
+    /// No single was found so the digit comes from the solution.
+    Solution,
+}
+
+impl std::fmt::Display for Technique {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::Solution => "solution",
+        };
+        write!(fmt, "{}", name)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hint {
+    pub position: Position,
+    pub value: u8,
+    pub technique: Technique,
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.technique {
+            Technique::NakedSingle => "it is the only candidate of the cell",
+            Technique::HiddenSingle => "it has no other place in a row, column or box",
+            Technique::Solution => "the solution says so",
+        };
+        write!(
+            fmt,
+            "r{}c{} is {} because {}",
+            self.position.y + 1,
+            self.position.x + 1,
+            self.value,
+            reason
+        )
+    }
+}
+
+/// Finds the next digit to place, preferring the ones a player can find by
+/// logic. Returns `None` if the grid is full or has no solution.
+pub fn hint(puzzle: &Puzzle) -> Option<Hint> {
+    // A grid without a solution may still have singles, but they would lead
+    // the player nowhere.
+    let mut solution = puzzle.clone();
+    if !solver::solve_puzzle(&mut solution) {
+        return None;
+    }
+
+    let candidates = CandidateGrid::from_puzzle(puzzle);
+    if let Some(hint) = naked_single(puzzle, &candidates) {
+        return Some(hint);
+    }
+    if let Some(hint) = hidden_single(puzzle, &candidates) {
+        return Some(hint);
+    }
+
+    let position = empty_cells(puzzle).min_by_key(|position| candidates.count(*position))?;
+    Some(Hint {
+        position,
+        value: solution.sudoku.value(position)?,
+        technique: Technique::Solution,
+    })
+}
+
+/// An empty cell with only one candidate.
+pub(super) fn naked_single(puzzle: &Puzzle, candidates: &CandidateGrid) -> Option<Hint> {
+    let position = empty_cells(puzzle).find(|position| candidates.count(*position) == 1)?;
+    Some(Hint {
+        position,
+        value: candidates.get(position)[0],
+        technique: Technique::NakedSingle,
+    })
+}
+
+/// A digit with only one empty cell left in a row, column or box.
+pub(super) fn hidden_single(puzzle: &Puzzle, candidates: &CandidateGrid) -> Option<Hint> {
+    for house in House::all() {
+        for value in 1..=SUDOKU_SIZE as u8 {
+            let places: Vec<Position> = house
+                .cells()
+                .filter(|position| {
+                    puzzle.sudoku.value(*position).is_none()
+                        && candidates.contains(*position, value)
+                })
+                .collect();
+            if places.len() == 1 {
+                return Some(Hint {
+                    position: places[0],
+                    value,
+                    technique: Technique::HiddenSingle,
+                });
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn empty_cells(puzzle: &Puzzle) -> impl Iterator<Item = Position> + '_ {
+    (0..SUDOKU_SIZE * SUDOKU_SIZE)
+        .map(|i| Position {
+            x: i % SUDOKU_SIZE,
+            y: i / SUDOKU_SIZE,
+        })
+        .filter(move |position| puzzle.sudoku.value(*position).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_naked_and_hidden_single() {
+        let puzzle: Puzzle = format!("12345678.{}", ".".repeat(72)).parse().unwrap();
+        assert_eq!(
+            Some(Hint {
+                position: Position { x: 8, y: 0 },
+                value: 9,
+                technique: Technique::NakedSingle,
+            }),
+            hint(&puzzle)
+        );
+
+        // The 1s in the other rows and columns leave one place for the 1 in the first box.
+        let puzzle: Puzzle = format!(
+            ".........\n...1.....\n......1..\n.1.......\n{}\n..1......\n{}",
+            ".".repeat(18),
+            ".".repeat(18)
+        )
+        .parse()
+        .unwrap();
+        let hint = hint(&puzzle).unwrap();
+        assert_eq!(Technique::HiddenSingle, hint.technique);
+        assert_eq!(Position { x: 0, y: 0 }, hint.position);
+        assert_eq!(1, hint.value);
+    }
+
+    #[test]
+    fn test_no_hint_without_solution() {
+        // r1c9 is a naked single but the two 5s in the last row conflict.
+        let puzzle: Puzzle = format!("12345678.{}55.......", ".".repeat(63))
+            .parse()
+            .unwrap();
+        assert_eq!(None, hint(&puzzle));
+    }
+}
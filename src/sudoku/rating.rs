@@ -0,0 +1,145 @@
+//! Difficulty rating by the techniques a player needs to solve a puzzle.
+
+use super::candidates::CandidateGrid;
+use super::hint::{self, Hint};
+use super::solver;
+use super::variant::Puzzle;
+use std::str::FromStr;
+
+/// Ordered from the easiest to the hardest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy = 1,
+    Medium = 2,
+    Hard = 3,
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        };
+        write!(fmt, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("unknown difficulty '{}'", s)),
+        }
+    }
+}
+
+impl Difficulty {
+    /// The level number used by the puzzle service, 1 for `Easy`.
+    pub fn level(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_level(level: u8) -> Option<Difficulty> {
+        match level {
+            1 => Some(Difficulty::Easy),
+            2 => Some(Difficulty::Medium),
+            3 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// The digits placed as naked singles while solving.
+    pub naked_singles: usize,
+    /// The digits placed as hidden singles while solving.
+    pub hidden_singles: usize,
+    /// `true` if singles were not enough and the rest needs guessing.
+    pub needs_guessing: bool,
+}
+
+/// Rates a puzzle by solving it the way a player would, looking for hidden
+/// singles first because they are easier to spot. A puzzle solved by hidden
+/// singles alone is `Easy`, one that also needs naked singles is `Medium`
+/// and one that needs guessing is `Hard`. Returns `None` if the puzzle has
+/// no solution.
+pub fn rate(puzzle: &Puzzle) -> Option<Rating> {
+    if solver::count_solutions(puzzle, 1) == 0 {
+        return None;
+    }
+
+    let mut rating = Rating {
+        difficulty: Difficulty::Easy,
+        naked_singles: 0,
+        hidden_singles: 0,
+        needs_guessing: false,
+    };
+    let mut puzzle = puzzle.clone();
+    loop {
+        let candidates = CandidateGrid::from_puzzle(&puzzle);
+        let place = |puzzle: &mut Puzzle, hint: Hint| {
+            puzzle.sudoku.set_value(hint.position, Some(hint.value));
+        };
+        if let Some(hint) = hint::hidden_single(&puzzle, &candidates) {
+            place(&mut puzzle, hint);
+            rating.hidden_singles += 1;
+        } else if let Some(hint) = hint::naked_single(&puzzle, &candidates) {
+            place(&mut puzzle, hint);
+            rating.naked_singles += 1;
+        } else {
+            rating.needs_guessing = hint::empty_cells(&puzzle).next().is_some();
+            break;
+        }
+    }
+
+    rating.difficulty = if rating.needs_guessing {
+        Difficulty::Hard
+    } else if rating.naked_singles > 0 {
+        Difficulty::Medium
+    } else {
+        Difficulty::Easy
+    };
+    Some(rating)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        let puzzle: Puzzle = "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n\
+                              7...2...6\n.6....28.\n...419..5\n....8..79\n"
+            .parse()
+            .unwrap();
+        let rating = rate(&puzzle).unwrap();
+        assert_eq!(rating.naked_singles + rating.hidden_singles, 51);
+        assert!(!rating.needs_guessing);
+        assert_ne!(rating.difficulty, Difficulty::Hard);
+
+        let empty: Puzzle = ".".repeat(81).parse().unwrap();
+        assert_eq!(rate(&empty).unwrap().difficulty, Difficulty::Hard);
+
+        // r1c9 has no candidate left.
+        let unsolvable: Puzzle = format!("12345678.\n........9\n{}", ".".repeat(63))
+            .parse()
+            .unwrap();
+        assert_eq!(rate(&unsolvable), None);
+    }
+
+    #[test]
+    fn test_difficulty_level() {
+        assert_eq!(Difficulty::from_level(2), Some(Difficulty::Medium));
+        assert_eq!(Difficulty::Hard.level(), 3);
+        assert_eq!("easy".parse(), Ok(Difficulty::Easy));
+        assert_eq!(Difficulty::Easy.to_string(), "Easy");
+    }
+}
@@ -0,0 +1,774 @@
+//! Rendering of puzzles to images. The puzzle is first laid out to a
+//! `Drawing` of simple shapes and the output formats only know how to draw
+//! those shapes, so every format looks the same.
+
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod svg;
+pub mod terminal;
+
+use super::candidates::CandidateGrid;
+use super::gattai::Gattai;
+use super::variant::{Allowed, Constraint, DotKind, Puzzle, Side};
+use super::{solver, Origin, Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+
+/// The size of a cell in drawing units. SVG uses them as pixels.
+pub const CELL_SIZE: f64 = 40.0;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+const BLACK: Color = Color(0, 0, 0);
+const WHITE: Color = Color(255, 255, 255);
+const GIVEN: Color = BLACK;
+const FILLED: Color = Color(26, 95, 180);
+const PENCIL_MARK: Color = Color(90, 90, 90);
+const HIGHLIGHT: Color = Color(255, 233, 168);
+const SHADE: Color = Color(210, 210, 210);
+const THIN_LINE: Color = Color(120, 120, 120);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// A line through `points`.
+    Line {
+        points: Vec<(f64, f64)>,
+        width: f64,
+        color: Color,
+    },
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: Option<Color>,
+        stroke: Option<(Color, f64)>,
+    },
+    Circle {
+        x: f64,
+        y: f64,
+        radius: f64,
+        fill: Option<Color>,
+        stroke: Option<(Color, f64)>,
+    },
+    /// Text centered at `x`, `y`.
+    Text {
+        x: f64,
+        y: f64,
+        size: f64,
+        text: String,
+        color: Color,
+        bold: bool,
+    },
+}
+
+/// Shapes in drawing units with the origin at the top left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drawing {
+    pub width: f64,
+    pub height: f64,
+    pub shapes: Vec<Shape>,
+}
+
+/// What to draw besides the grid and the digits.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub title: Option<String>,
+    pub id: Option<String>,
+    /// The puzzle before it was solved. The digits that are not in it are
+    /// drawn as filled in. Without it the origin of the digits tells them
+    /// apart.
+    pub givens: Option<Sudoku>,
+    /// Draw the candidates of the empty cells.
+    pub pencil_marks: bool,
+    /// Pencil marks to draw instead of the computed candidates, for example
+    /// the notes of a player.
+    pub notes: Option<CandidateGrid>,
+    /// Cells to highlight, for example the ones a hint refers to.
+    pub highlight: Vec<Position>,
+}
+
+struct BoardCell {
+    value: Option<u8>,
+    is_given: bool,
+    candidates: Vec<u8>,
+}
+
+/// The cells of a puzzle in a rectangle. Cells outside of all the grids of
+/// a gattai puzzle are `None`.
+struct Board {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<BoardCell>>,
+}
+
+impl Board {
+    fn cell(&self, position: Position) -> Option<&BoardCell> {
+        if position.x < self.width && position.y < self.height {
+            self.cells[position.y * self.width + position.x].as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+pub fn draw_sudoku(sudoku: &Sudoku, options: &Options) -> Drawing {
+    draw_puzzle(
+        &Puzzle {
+            sudoku: *sudoku,
+            constraints: Vec::new(),
+        },
+        options,
+    )
+}
+
+pub fn draw_puzzle(puzzle: &Puzzle, options: &Options) -> Drawing {
+    let mut cells = Vec::with_capacity(SUDOKU_SIZE * SUDOKU_SIZE);
+    for y in 0..SUDOKU_SIZE {
+        for x in 0..SUDOKU_SIZE {
+            let position = Position { x, y };
+            let value = position.value(&puzzle.sudoku.cells);
+            let is_given = match options.givens {
+                Some(givens) => position.value(&givens.cells).is_some(),
+                None => puzzle.sudoku.is_given(y, x),
+            };
+            let candidates = match (value, &options.notes) {
+                (None, Some(notes)) if options.pencil_marks => notes.get(Position { x, y }),
+                (None, None) if options.pencil_marks => solver::candidates(puzzle, x, y),
+                _ => Vec::new(),
+            };
+            cells.push(Some(BoardCell {
+                value,
+                is_given,
+                candidates,
+            }));
+        }
+    }
+
+    let board = Board {
+        width: SUDOKU_SIZE,
+        height: SUDOKU_SIZE,
+        cells,
+    };
+    draw(&board, &puzzle.constraints, options)
+}
+
+pub fn draw_gattai(gattai: &Gattai, options: &Options) -> Drawing {
+    let mut cells = Vec::with_capacity(gattai.width() * gattai.height());
+    for y in 0..gattai.height() {
+        for x in 0..gattai.width() {
+            let position = Position { x, y };
+            cells.push(if gattai.contains(position) {
+                Some(BoardCell {
+                    value: gattai.get(position),
+                    is_given: gattai.origin(position) == Some(Origin::Given),
+                    candidates: match options.pencil_marks {
+                        true => gattai.candidates(position),
+                        false => Vec::new(),
+                    },
+                })
+            } else {
+                None
+            });
+        }
+    }
+
+    let board = Board {
+        width: gattai.width(),
+        height: gattai.height(),
+        cells,
+    };
+    draw(&board, &[], options)
+}
+
+fn draw(board: &Board, constraints: &[Constraint], options: &Options) -> Drawing {
+    let has_outside_clues = constraints.iter().any(|constraint| {
+        matches!(
+            constraint,
+            Constraint::Sandwich(_)
+                | Constraint::Skyscraper(_)
+                | Constraint::XSum(_)
+                | Constraint::LittleKiller(_)
+        )
+    });
+    let margin = if has_outside_clues {
+        CELL_SIZE
+    } else {
+        CELL_SIZE / 2.0
+    };
+    let header = if options.title.is_some() || options.id.is_some() {
+        CELL_SIZE
+    } else {
+        0.0
+    };
+
+    // The title goes above the margin so it does not hit the clues on the top.
+    let mut canvas = Canvas {
+        left: margin,
+        top: header + margin,
+        shapes: Vec::new(),
+    };
+    let width = 2.0 * margin + board.width as f64 * CELL_SIZE;
+    let height = 2.0 * margin + header + board.height as f64 * CELL_SIZE;
+
+    canvas.shapes.push(Shape::Rect {
+        x: 0.0,
+        y: 0.0,
+        width,
+        height,
+        fill: Some(WHITE),
+        stroke: None,
+    });
+    draw_header(&mut canvas, options, margin, width);
+
+    for position in options.highlight.iter() {
+        if board.cell(*position).is_some() {
+            canvas.fill_cell(*position, HIGHLIGHT);
+        }
+    }
+    for constraint in constraints {
+        draw_constraint(&mut canvas, constraint);
+    }
+    draw_grid(&mut canvas, board);
+    for constraint in constraints {
+        draw_marker(&mut canvas, constraint);
+    }
+    draw_digits(&mut canvas, board);
+
+    Drawing {
+        width,
+        height,
+        shapes: canvas.shapes,
+    }
+}
+
+struct Canvas {
+    /// The top left corner of the grid.
+    left: f64,
+    top: f64,
+    shapes: Vec<Shape>,
+}
+
+impl Canvas {
+    fn corner(&self, x: isize, y: isize) -> (f64, f64) {
+        (
+            self.left + x as f64 * CELL_SIZE,
+            self.top + y as f64 * CELL_SIZE,
+        )
+    }
+
+    fn center(&self, position: Position) -> (f64, f64) {
+        self.center_of(position.x as isize, position.y as isize)
+    }
+
+    /// The center of a cell that may also be outside the grid.
+    fn center_of(&self, x: isize, y: isize) -> (f64, f64) {
+        let (left, top) = self.corner(x, y);
+        (left + CELL_SIZE / 2.0, top + CELL_SIZE / 2.0)
+    }
+
+    fn fill_cell(&mut self, position: Position, color: Color) {
+        let (x, y) = self.corner(position.x as isize, position.y as isize);
+        self.shapes.push(Shape::Rect {
+            x,
+            y,
+            width: CELL_SIZE,
+            height: CELL_SIZE,
+            fill: Some(color),
+            stroke: None,
+        });
+    }
+
+    /// A line through the centers of `cells`. Nothing is drawn for fewer
+    /// than two cells.
+    fn line(&mut self, cells: &[Position], width: f64, color: Color) {
+        if cells.len() < 2 {
+            return;
+        }
+        let points = cells.iter().map(|cell| self.center(*cell)).collect();
+        self.shapes.push(Shape::Line {
+            points,
+            width,
+            color,
+        });
+    }
+
+    fn text(&mut self, (x, y): (f64, f64), size: f64, text: String, color: Color, bold: bool) {
+        self.shapes.push(Shape::Text {
+            x,
+            y,
+            size,
+            text,
+            color,
+            bold,
+        });
+    }
+}
+
+fn draw_header(canvas: &mut Canvas, options: &Options, margin: f64, width: f64) {
+    let y = CELL_SIZE / 2.0;
+    if let Some(title) = &options.title {
+        canvas.text(
+            (width / 2.0, y),
+            CELL_SIZE * 0.5,
+            title.clone(),
+            BLACK,
+            true,
+        );
+    }
+    if let Some(id) = &options.id {
+        let x = width - margin - CELL_SIZE;
+        canvas.text(
+            (x, y),
+            CELL_SIZE * 0.3,
+            format!("#{}", id),
+            THIN_LINE,
+            false,
+        );
+    }
+}
+
+fn draw_grid(canvas: &mut Canvas, board: &Board) {
+    for y in 0..board.height {
+        for x in 0..board.width {
+            if board.cell(Position { x, y }).is_some() {
+                let (left, top) = canvas.corner(x as isize, y as isize);
+                canvas.shapes.push(Shape::Rect {
+                    x: left,
+                    y: top,
+                    width: CELL_SIZE,
+                    height: CELL_SIZE,
+                    fill: None,
+                    stroke: Some((THIN_LINE, 1.0)),
+                });
+            }
+        }
+    }
+
+    for box_y in 0..board.height / BOX_SIZE {
+        for box_x in 0..board.width / BOX_SIZE {
+            let corner = Position {
+                x: box_x * BOX_SIZE,
+                y: box_y * BOX_SIZE,
+            };
+            if board.cell(corner).is_some() {
+                let (left, top) = canvas.corner(corner.x as isize, corner.y as isize);
+                canvas.shapes.push(Shape::Rect {
+                    x: left,
+                    y: top,
+                    width: CELL_SIZE * BOX_SIZE as f64,
+                    height: CELL_SIZE * BOX_SIZE as f64,
+                    fill: None,
+                    stroke: Some((BLACK, 3.0)),
+                });
+            }
+        }
+    }
+}
+
+fn draw_digits(canvas: &mut Canvas, board: &Board) {
+    for y in 0..board.height {
+        for x in 0..board.width {
+            let position = Position { x, y };
+            let cell = match board.cell(position) {
+                Some(cell) => cell,
+                None => continue,
+            };
+            let center = canvas.center(position);
+
+            if let Some(value) = cell.value {
+                let color = if cell.is_given { GIVEN } else { FILLED };
+                canvas.text(
+                    center,
+                    CELL_SIZE * 0.6,
+                    value.to_string(),
+                    color,
+                    cell.is_given,
+                );
+                continue;
+            }
+
+            let step = CELL_SIZE / BOX_SIZE as f64;
+            for candidate in cell.candidates.iter() {
+                let index = (*candidate - 1) as usize;
+                let offset_x = (index % BOX_SIZE) as f64 - 1.0;
+                let offset_y = (index / BOX_SIZE) as f64 - 1.0;
+                canvas.text(
+                    (center.0 + offset_x * step, center.1 + offset_y * step),
+                    CELL_SIZE * 0.25,
+                    candidate.to_string(),
+                    PENCIL_MARK,
+                    false,
+                );
+            }
+        }
+    }
+}
+
+/// Draws the parts of a constraint that go below the grid lines and digits.
+fn draw_constraint(canvas: &mut Canvas, constraint: &Constraint) {
+    match constraint {
+        Constraint::Thermometer(thermometer) => {
+            let bulb = match thermometer.cells.first() {
+                Some(bulb) => *bulb,
+                None => return,
+            };
+            canvas.line(&thermometer.cells, CELL_SIZE * 0.3, SHADE);
+            let (x, y) = canvas.center(bulb);
+            canvas.shapes.push(Shape::Circle {
+                x,
+                y,
+                radius: CELL_SIZE * 0.35,
+                fill: Some(SHADE),
+                stroke: None,
+            });
+        }
+        Constraint::Arrow(arrow) => {
+            let (x, y) = canvas.center(arrow.circle);
+            let radius = CELL_SIZE * 0.4;
+            canvas.shapes.push(Shape::Circle {
+                x,
+                y,
+                radius,
+                fill: None,
+                stroke: Some((THIN_LINE, 2.0)),
+            });
+            if arrow.arrow.is_empty() {
+                return;
+            }
+
+            let mut points: Vec<(f64, f64)> = arrow
+                .arrow
+                .iter()
+                .map(|cell| canvas.center(*cell))
+                .collect();
+            let (first_x, first_y) = points[0];
+            let (dx, dy) = unit(first_x - x, first_y - y);
+            points.insert(0, (x + dx * radius, y + dy * radius));
+
+            let end = points[points.len() - 1];
+            let before = points[points.len() - 2];
+            let (dx, dy) = unit(end.0 - before.0, end.1 - before.1);
+            let head = CELL_SIZE * 0.2;
+            canvas.shapes.push(Shape::Line {
+                points,
+                width: 2.0,
+                color: THIN_LINE,
+            });
+            canvas.shapes.push(Shape::Line {
+                points: vec![
+                    (end.0 - head * (dx + dy), end.1 - head * (dy - dx)),
+                    end,
+                    (end.0 - head * (dx - dy), end.1 - head * (dy + dx)),
+                ],
+                width: 2.0,
+                color: THIN_LINE,
+            });
+        }
+        Constraint::GermanWhispers(whispers) => {
+            canvas.line(&whispers.cells, CELL_SIZE * 0.2, Color(100, 200, 100))
+        }
+        Constraint::Renban(renban) => {
+            canvas.line(&renban.cells, CELL_SIZE * 0.25, Color(210, 160, 240))
+        }
+        Constraint::Palindrome(palindrome) => {
+            canvas.line(&palindrome.cells, CELL_SIZE * 0.15, Color(160, 160, 160))
+        }
+        Constraint::CellRestriction(restriction) => {
+            for cell in restriction.cells.iter() {
+                let (x, y) = canvas.center(*cell);
+                match &restriction.allowed {
+                    Allowed::Even => {
+                        let size = CELL_SIZE * 0.7;
+                        canvas.shapes.push(Shape::Rect {
+                            x: x - size / 2.0,
+                            y: y - size / 2.0,
+                            width: size,
+                            height: size,
+                            fill: Some(SHADE),
+                            stroke: None,
+                        });
+                    }
+                    Allowed::Odd => canvas.shapes.push(Shape::Circle {
+                        x,
+                        y,
+                        radius: CELL_SIZE * 0.35,
+                        fill: Some(SHADE),
+                        stroke: None,
+                    }),
+                    Allowed::Digits(digits) => {
+                        let text: String = digits.iter().map(|digit| digit.to_string()).collect();
+                        let top = y - CELL_SIZE * 0.35;
+                        canvas.text((x, top), CELL_SIZE * 0.2, text, THIN_LINE, false);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Draws the parts of a constraint that go on top of the grid lines.
+fn draw_marker(canvas: &mut Canvas, constraint: &Constraint) {
+    match constraint {
+        Constraint::Dot(dot) => {
+            let (x, y) = midpoint(canvas, dot.cells.0, dot.cells.1);
+            match dot.kind {
+                DotKind::White | DotKind::Black => canvas.shapes.push(Shape::Circle {
+                    x,
+                    y,
+                    radius: CELL_SIZE * 0.12,
+                    fill: Some(if dot.kind == DotKind::White {
+                        WHITE
+                    } else {
+                        BLACK
+                    }),
+                    stroke: Some((BLACK, 1.0)),
+                }),
+                DotKind::X | DotKind::V => {
+                    let text = if dot.kind == DotKind::X { "X" } else { "V" };
+                    canvas.text((x, y), CELL_SIZE * 0.35, text.to_string(), BLACK, true)
+                }
+            }
+        }
+        Constraint::GreaterThan(greater) => {
+            // A chevron pointing to the smaller cell.
+            let (x, y) = midpoint(canvas, greater.greater, greater.smaller);
+            let (from_x, from_y) = canvas.center(greater.greater);
+            let (to_x, to_y) = canvas.center(greater.smaller);
+            let (dx, dy) = unit(to_x - from_x, to_y - from_y);
+            let size = CELL_SIZE * 0.12;
+            canvas.shapes.push(Shape::Line {
+                points: vec![
+                    (x - dx * size - dy * size, y - dy * size + dx * size),
+                    (x + dx * size, y + dy * size),
+                    (x - dx * size + dy * size, y - dy * size - dx * size),
+                ],
+                width: 2.0,
+                color: BLACK,
+            });
+        }
+        Constraint::Sandwich(_) | Constraint::Skyscraper(_) | Constraint::XSum(_) => {
+            // The letter of the kind goes before the number, the same as in
+            // the text output, and each kind has its own line of the margin
+            // cell so that clues of different kinds next to the same row or
+            // column do not overlap.
+            let (location, value) = constraint.outside_clue().unwrap();
+            let kind = constraint.outside_clue_label().unwrap();
+            let line = match constraint {
+                Constraint::Sandwich(_) => -1.0,
+                Constraint::Skyscraper(_) => 0.0,
+                _ => 1.0,
+            };
+            let index = location.index as isize;
+            let last = SUDOKU_SIZE as isize;
+            let (x, y) = match location.side {
+                Side::Top => canvas.center_of(index, -1),
+                Side::Bottom => canvas.center_of(index, last),
+                Side::Left => canvas.center_of(-1, index),
+                Side::Right => canvas.center_of(last, index),
+            };
+            canvas.text(
+                (x, y + line * CELL_SIZE * 0.3),
+                CELL_SIZE * 0.28,
+                format!("{}{}", kind, value),
+                BLACK,
+                false,
+            );
+        }
+        Constraint::LittleKiller(killer) => {
+            let (dx, dy) = killer.direction.step();
+            let start = killer.start;
+            let (x, y) = canvas.center_of(start.x as isize - dx, start.y as isize - dy);
+            canvas.text(
+                (x, y),
+                CELL_SIZE * 0.35,
+                killer.sum.to_string(),
+                BLACK,
+                false,
+            );
+
+            let (dx, dy) = (dx as f64, dy as f64);
+            let tip = (x + dx * CELL_SIZE * 0.5, y + dy * CELL_SIZE * 0.5);
+            canvas.shapes.push(Shape::Line {
+                points: vec![(x + dx * CELL_SIZE * 0.25, y + dy * CELL_SIZE * 0.25), tip],
+                width: 1.0,
+                color: BLACK,
+            });
+        }
+        _ => {}
+    }
+}
+
+fn midpoint(canvas: &Canvas, a: Position, b: Position) -> (f64, f64) {
+    let (ax, ay) = canvas.center(a);
+    let (bx, by) = canvas.center(b);
+    ((ax + bx) / 2.0, (ay + by) / 2.0)
+}
+
+fn unit(dx: f64, dy: f64) -> (f64, f64) {
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (dx / length, dy / length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::variant::{Arrow, Thermometer};
+    use super::*;
+
+    fn texts(drawing: &Drawing) -> Vec<(&str, Color, bool)> {
+        drawing
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text {
+                    text, color, bold, ..
+                } => Some((text.as_str(), *color, *bold)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw_sudoku_size() {
+        let drawing = draw_sudoku(&Sudoku::empty(), &Options::default());
+        assert_eq!(10.0 * CELL_SIZE, drawing.width);
+        assert_eq!(10.0 * CELL_SIZE, drawing.height);
+        assert!(texts(&drawing).is_empty());
+
+        // The title adds a row above the grid.
+        let options = Options {
+            title: Some(String::from("Title")),
+            ..Options::default()
+        };
+        let drawing = draw_sudoku(&Sudoku::empty(), &options);
+        assert_eq!(11.0 * CELL_SIZE, drawing.height);
+        assert_eq!(vec![("Title", BLACK, true)], texts(&drawing));
+    }
+
+    #[test]
+    fn test_draw_givens_and_filled() {
+        let mut sudoku = Sudoku::empty();
+        sudoku.set(0, 0, 1);
+        sudoku.place(0, 1, 2);
+        let drawing = draw_sudoku(&sudoku, &Options::default());
+        assert_eq!(
+            vec![("1", GIVEN, true), ("2", FILLED, false)],
+            texts(&drawing)
+        );
+
+        // With the givens in the options every other digit is filled in.
+        let options = Options {
+            givens: Some(Sudoku::empty()),
+            ..Options::default()
+        };
+        let drawing = draw_sudoku(&sudoku, &options);
+        assert!(texts(&drawing)
+            .iter()
+            .all(|(_, color, bold)| *color == FILLED && !bold));
+    }
+
+    #[test]
+    fn test_draw_solved_gattai() {
+        let mut twodoku = Gattai::twodoku();
+        twodoku.set(Position { x: 0, y: 0 }, Some(1)).unwrap();
+        assert!(twodoku.solve());
+        let drawing = draw_gattai(&twodoku, &Options::default());
+        let texts = texts(&drawing);
+        assert_eq!(("1", GIVEN, true), texts[0]);
+        assert!(texts[1..]
+            .iter()
+            .all(|(_, color, bold)| *color == FILLED && !bold));
+    }
+
+    #[test]
+    fn test_draw_pencil_marks_and_highlight() {
+        let puzzle: Puzzle = format!("12345678.{}", ".".repeat(72)).parse().unwrap();
+        let options = Options {
+            pencil_marks: true,
+            highlight: vec![Position { x: 8, y: 0 }],
+            ..Options::default()
+        };
+        let drawing = draw_puzzle(&puzzle, &options);
+        let marks: Vec<&str> = texts(&drawing)
+            .iter()
+            .filter(|(_, color, _)| *color == PENCIL_MARK)
+            .map(|(text, _, _)| *text)
+            .collect();
+        // r1c9 can only be 9, the other empty cells have several candidates.
+        assert!(marks.contains(&"9"));
+        assert!(marks.len() > 72);
+
+        // The grid starts half a cell from the edges.
+        let (x, y) = (8.5 * CELL_SIZE, 0.5 * CELL_SIZE);
+        assert!(drawing.shapes.contains(&Shape::Rect {
+            x,
+            y,
+            width: CELL_SIZE,
+            height: CELL_SIZE,
+            fill: Some(HIGHLIGHT),
+            stroke: None,
+        }));
+    }
+
+    #[test]
+    fn test_draw_outside_clues_margin() {
+        let puzzle: Puzzle = format!("{}\nsandwich top 1 10", ".".repeat(81))
+            .parse()
+            .unwrap();
+        let drawing = draw_puzzle(&puzzle, &Options::default());
+        assert_eq!(11.0 * CELL_SIZE, drawing.width);
+        assert!(texts(&drawing).contains(&("S10", BLACK, false)));
+    }
+
+    #[test]
+    fn test_draw_outside_clues_of_two_kinds() {
+        let puzzle: Puzzle = format!("{}\nsandwich left 3 15\nxsum left 3 15", ".".repeat(81))
+            .parse()
+            .unwrap();
+        let drawing = draw_puzzle(&puzzle, &Options::default());
+        let clues: Vec<(&str, f64, f64)> = drawing
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text { text, x, y, .. } => Some((text.as_str(), *x, *y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, clues.len());
+        assert_eq!(("S15", "X15"), (clues[0].0, clues[1].0));
+        assert_eq!(clues[0].1, clues[1].1);
+        assert!(clues[1].2 - clues[0].2 >= CELL_SIZE * 0.5);
+    }
+
+    #[test]
+    fn test_draw_empty_lines() {
+        let puzzle = Puzzle {
+            sudoku: Sudoku::empty(),
+            constraints: vec![
+                Constraint::Thermometer(Thermometer { cells: Vec::new() }),
+                Constraint::Arrow(Arrow {
+                    circle: Position { x: 4, y: 4 },
+                    arrow: Vec::new(),
+                }),
+            ],
+        };
+        let drawing = draw_puzzle(&puzzle, &Options::default());
+        let circles = drawing
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape, Shape::Circle { .. }))
+            .count();
+        assert_eq!(1, circles);
+        assert!(!drawing
+            .shapes
+            .iter()
+            .any(|shape| matches!(shape, Shape::Line { .. })));
+    }
+}
@@ -0,0 +1,450 @@
+//! PDF output. The writer only uses the standard Helvetica font which every
+//! PDF reader has, so nothing needs to be embedded. The text is limited to
+//! the Latin-1 characters of the WinAnsi encoding.
+
+use super::super::rating::{self, Rating};
+use super::super::solver;
+use super::super::variant::Puzzle;
+use super::{draw_puzzle, Color, Drawing, Options, Shape};
+use std::fmt::Write;
+
+/// A4 in points.
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const PAGE_MARGIN: f64 = 36.0;
+const FOOTER: f64 = 24.0;
+
+/// How many puzzles there are on a page, as columns and rows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PageLayout {
+    One,
+    Two,
+    Four,
+    Six,
+}
+
+impl PageLayout {
+    pub fn from_count(count: usize) -> Option<PageLayout> {
+        match count {
+            1 => Some(PageLayout::One),
+            2 => Some(PageLayout::Two),
+            4 => Some(PageLayout::Four),
+            6 => Some(PageLayout::Six),
+            _ => None,
+        }
+    }
+
+    fn grid(self) -> (usize, usize) {
+        match self {
+            PageLayout::One => (1, 1),
+            PageLayout::Two => (1, 2),
+            PageLayout::Four => (2, 2),
+            PageLayout::Six => (2, 3),
+        }
+    }
+
+    fn per_page(self) -> usize {
+        let (columns, rows) = self.grid();
+        columns * rows
+    }
+}
+
+/// A booklet with the puzzles first and their solutions on the pages after
+/// them. The title of a puzzle has the difficulty of its rating, and the
+/// puzzles without one are rated with `rating::rate`. The solutions are found
+/// with `solver::solve_puzzle` and a puzzle without a solution is drawn
+/// unsolved in its place with a title that says so.
+pub fn booklet(puzzles: &[(Puzzle, Option<Rating>)], layout: PageLayout) -> Vec<u8> {
+    let mut pages: Vec<Vec<Drawing>> = Vec::new();
+
+    let puzzle_drawings = puzzles.iter().enumerate().map(|(index, (puzzle, rating))| {
+        let title = match rating.or_else(|| rating::rate(puzzle)) {
+            Some(rating) => format!("Puzzle {} - {}", index + 1, rating.difficulty),
+            None => format!("Puzzle {}", index + 1),
+        };
+        let options = Options {
+            title: Some(title),
+            ..Options::default()
+        };
+        draw_puzzle(puzzle, &options)
+    });
+    let solution_drawings = puzzles.iter().enumerate().map(|(index, (puzzle, _))| {
+        let mut solution = puzzle.clone();
+        let title = if solver::solve_puzzle(&mut solution) {
+            format!("Solution {}", index + 1)
+        } else {
+            format!("Puzzle {} has no solution", index + 1)
+        };
+        let options = Options {
+            title: Some(title),
+            givens: Some(puzzle.sudoku),
+            ..Options::default()
+        };
+        draw_puzzle(&solution, &options)
+    });
+
+    for drawings in [
+        puzzle_drawings.collect::<Vec<_>>(),
+        solution_drawings.collect(),
+    ] {
+        for chunk in drawings.chunks(layout.per_page()) {
+            pages.push(chunk.to_vec());
+        }
+    }
+
+    let contents: Vec<String> = pages
+        .iter()
+        .enumerate()
+        .map(|(index, drawings)| page_content(drawings, layout, index + 1))
+        .collect();
+    write(&contents)
+}
+
+fn page_content(drawings: &[Drawing], layout: PageLayout, page_number: usize) -> String {
+    let (columns, rows) = layout.grid();
+    let slot_width = (PAGE_WIDTH - 2.0 * PAGE_MARGIN) / columns as f64;
+    let slot_height = (PAGE_HEIGHT - 2.0 * PAGE_MARGIN - FOOTER) / rows as f64;
+
+    let mut content = String::new();
+    for (index, drawing) in drawings.iter().enumerate() {
+        let scale = (slot_width / drawing.width).min(slot_height / drawing.height);
+        let left = PAGE_MARGIN
+            + (index % columns) as f64 * slot_width
+            + (slot_width - drawing.width * scale) / 2.0;
+        let top = PAGE_MARGIN
+            + (index / columns) as f64 * slot_height
+            + (slot_height - drawing.height * scale) / 2.0;
+        let transform = Transform { left, top, scale };
+        for shape in drawing.shapes.iter() {
+            write_shape(&mut content, shape, &transform);
+        }
+    }
+
+    let footer = Transform {
+        left: 0.0,
+        top: 0.0,
+        scale: 1.0,
+    };
+    write_shape(
+        &mut content,
+        &Shape::Text {
+            x: PAGE_WIDTH / 2.0,
+            y: PAGE_HEIGHT - PAGE_MARGIN / 2.0 - FOOTER / 2.0,
+            size: 10.0,
+            text: page_number.to_string(),
+            color: Color(0, 0, 0),
+            bold: false,
+        },
+        &footer,
+    );
+    content
+}
+
+/// Places a drawing on the page. Drawings have the origin at the top left
+/// corner and PDF pages at the bottom left corner.
+struct Transform {
+    left: f64,
+    top: f64,
+    scale: f64,
+}
+
+impl Transform {
+    fn point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.left + x * self.scale,
+            PAGE_HEIGHT - (self.top + y * self.scale),
+        )
+    }
+}
+
+fn write_shape(content: &mut String, shape: &Shape, transform: &Transform) {
+    let scale = transform.scale;
+    match shape {
+        Shape::Line {
+            points,
+            width,
+            color,
+        } => {
+            writeln!(content, "{} w 1 J 1 j {} RG", width * scale, rgb(*color)).unwrap();
+            for (index, point) in points.iter().enumerate() {
+                let (x, y) = transform.point(*point);
+                let operator = if index == 0 { "m" } else { "l" };
+                writeln!(content, "{:.2} {:.2} {}", x, y, operator).unwrap();
+            }
+            content.push_str("S\n");
+        }
+        Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            stroke,
+        } => {
+            let (left, bottom) = transform.point((*x, *y + *height));
+            let path = format!(
+                "{:.2} {:.2} {:.2} {:.2} re",
+                left,
+                bottom,
+                width * scale,
+                height * scale
+            );
+            paint(content, &path, *fill, *stroke, scale);
+        }
+        Shape::Circle {
+            x,
+            y,
+            radius,
+            fill,
+            stroke,
+        } => {
+            // Four Bézier curves are close enough to a circle.
+            let (cx, cy) = transform.point((*x, *y));
+            let r = radius * scale;
+            let k = 0.5523 * r;
+            let path = format!(
+                "{:.2} {:.2} m\n\
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n\
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n\
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n\
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c",
+                cx + r,
+                cy,
+                cx + r,
+                cy + k,
+                cx + k,
+                cy + r,
+                cx,
+                cy + r,
+                cx - k,
+                cy + r,
+                cx - r,
+                cy + k,
+                cx - r,
+                cy,
+                cx - r,
+                cy - k,
+                cx - k,
+                cy - r,
+                cx,
+                cy - r,
+                cx + k,
+                cy - r,
+                cx + r,
+                cy - k,
+                cx + r,
+                cy
+            );
+            paint(content, &path, *fill, *stroke, scale);
+        }
+        Shape::Text {
+            x,
+            y,
+            size,
+            text,
+            color,
+            bold,
+        } => {
+            let size = size * scale;
+            let (cx, cy) = transform.point((*x, *y));
+            let left = cx - text_width(text) * size / 2.0;
+            // Digits are about 0.7 em high so this puts their middle to `y`.
+            let baseline = cy - 0.35 * size;
+            writeln!(
+                content,
+                "BT /{} {:.2} Tf {} rg {:.2} {:.2} Td ({}) Tj ET",
+                if *bold { "F2" } else { "F1" },
+                size,
+                rgb(*color),
+                left,
+                baseline,
+                escape(text)
+            )
+            .unwrap();
+        }
+    }
+}
+
+fn paint(
+    content: &mut String,
+    path: &str,
+    fill: Option<Color>,
+    stroke: Option<(Color, f64)>,
+    scale: f64,
+) {
+    if let Some(color) = fill {
+        writeln!(content, "{} rg\n{}\nf", rgb(color), path).unwrap();
+    }
+    if let Some((color, width)) = stroke {
+        writeln!(
+            content,
+            "{} w {} RG\n{}\nS",
+            width * scale,
+            rgb(color),
+            path
+        )
+        .unwrap();
+    }
+}
+
+/// The width of `text` in Helvetica in em. Digits are exact and the rest
+/// is close enough for centering.
+fn text_width(text: &str) -> f64 {
+    text.chars()
+        .map(|c| match c {
+            '0'..='9' => 0.556,
+            ' ' => 0.278,
+            '-' => 0.333,
+            'A'..='Z' => 0.667,
+            _ => 0.5,
+        })
+        .sum()
+}
+
+fn rgb(Color(r, g, b): Color) -> String {
+    format!(
+        "{:.3} {:.3} {:.3}",
+        r as f64 / 255.0,
+        g as f64 / 255.0,
+        b as f64 / 255.0
+    )
+}
+
+/// Escapes `text` for a PDF string in the WinAnsi encoding. Latin-1 has the
+/// same codes in it and the other characters are written as '?'.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            '\u{a0}'..='\u{ff}' => format!("\\{:03o}", c as u32),
+            _ => String::from("?"),
+        })
+        .collect()
+}
+
+/// Writes a PDF document with one page for each content stream.
+fn write(contents: &[String]) -> Vec<u8> {
+    // 1 is the catalog, 2 the page tree, 3 and 4 the fonts and then a page
+    // and its content stream for every page.
+    let page_ids: Vec<usize> = (0..contents.len()).map(|index| 5 + 2 * index).collect();
+    let mut objects: Vec<String> = vec![
+        String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            contents.len()
+        ),
+        String::from(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        ),
+        String::from(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        ),
+    ];
+    for (content, page_id) in contents.iter().zip(page_ids.iter()) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object).unwrap();
+    }
+
+    let xref = pdf.len();
+    write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(pdf, "{:010} 00000 n ", offset).unwrap();
+    }
+    write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    )
+    .unwrap();
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::rating::Difficulty;
+    use super::*;
+
+    #[test]
+    fn test_booklet() {
+        let puzzle: Puzzle = format!("123{}", ".".repeat(78)).parse().unwrap();
+        let puzzles = vec![(puzzle, None); 5];
+        let pdf = String::from_utf8(booklet(&puzzles, PageLayout::Four)).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        // Two pages of puzzles and two pages of solutions.
+        assert!(pdf.contains("/Count 4"));
+        // The rating of an almost empty grid, not a requested difficulty.
+        assert!(pdf.contains("(Puzzle 5 - Hard) Tj"));
+        assert!(pdf.contains("(Solution 1) Tj"));
+        assert!(pdf.contains("(4) Tj"));
+
+        // Every cross reference points to the start of its object.
+        let xref = pdf.rfind("xref\n").unwrap();
+        let entries = pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "));
+        for (index, entry) in entries.enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", index + 1)));
+        }
+    }
+
+    #[test]
+    fn test_booklet_unsolvable() {
+        let puzzle: Puzzle = format!("11{}", ".".repeat(79)).parse().unwrap();
+        let pdf = String::from_utf8(booklet(&[(puzzle, None)], PageLayout::One)).unwrap();
+        assert!(pdf.contains("(Puzzle 1) Tj"));
+        assert!(pdf.contains("(Puzzle 1 has no solution) Tj"));
+        assert!(!pdf.contains("(Solution 1) Tj"));
+    }
+
+    #[test]
+    fn test_booklet_rated_variant() {
+        let puzzle: Puzzle = format!("{}\nsandwich top 3 15", ".".repeat(81))
+            .parse()
+            .unwrap();
+        let rating = Rating {
+            difficulty: Difficulty::Easy,
+            naked_singles: 0,
+            hidden_singles: 0,
+            needs_guessing: false,
+        };
+        let pdf = String::from_utf8(booklet(&[(puzzle, Some(rating))], PageLayout::One)).unwrap();
+        // The given rating is used as it is.
+        assert!(pdf.contains("(Puzzle 1 - Easy) Tj"));
+        assert!(pdf.contains("(Solution 1) Tj"));
+        assert_eq!(2, pdf.matches("(S15) Tj").count());
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("\\(a\\) caf\\351 ?", escape("(a) café ☺"));
+    }
+}
@@ -0,0 +1,224 @@
+//! PNG output. The text is drawn with an embedded DejaVu Sans so no fonts
+//! need to be installed. Bold text is the same font with an outline.
+
+use super::{Color, Drawing, Shape};
+use tiny_skia::{
+    FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Rect, Stroke, Transform,
+};
+use ttf_parser::{Face, OutlineBuilder};
+
+static FONT: &[u8] = include_bytes!("../../../assets/DejaVuSans.ttf");
+
+lazy_static! {
+    static ref FACE: Face<'static> = Face::parse(FONT, 0).expect("embedded font is valid");
+}
+
+/// Renders the drawing with `scale` pixels per drawing unit. With scale 1 a
+/// cell is `CELL_SIZE` pixels.
+pub fn write(drawing: &Drawing, scale: f32) -> Result<Vec<u8>, String> {
+    let width = (drawing.width as f32 * scale).ceil() as u32;
+    let height = (drawing.height as f32 * scale).ceil() as u32;
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| format!("invalid image size {}x{}", width, height))?;
+
+    let transform = Transform::from_scale(scale, scale);
+    for shape in drawing.shapes.iter() {
+        draw_shape(&mut pixmap, shape, transform);
+    }
+
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+fn draw_shape(pixmap: &mut Pixmap, shape: &Shape, transform: Transform) {
+    match shape {
+        Shape::Line {
+            points,
+            width,
+            color,
+        } => {
+            let mut builder = PathBuilder::new();
+            for (index, (x, y)) in points.iter().enumerate() {
+                if index == 0 {
+                    builder.move_to(*x as f32, *y as f32);
+                } else {
+                    builder.line_to(*x as f32, *y as f32);
+                }
+            }
+            if let Some(path) = builder.finish() {
+                stroke(pixmap, &path, *color, *width as f32, transform);
+            }
+        }
+        Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            stroke: outline,
+        } => {
+            let rect = Rect::from_xywh(*x as f32, *y as f32, *width as f32, *height as f32);
+            if let Some(path) = rect.map(PathBuilder::from_rect) {
+                paint_path(pixmap, &path, *fill, *outline, transform);
+            }
+        }
+        Shape::Circle {
+            x,
+            y,
+            radius,
+            fill,
+            stroke: outline,
+        } => {
+            if let Some(path) = PathBuilder::from_circle(*x as f32, *y as f32, *radius as f32) {
+                paint_path(pixmap, &path, *fill, *outline, transform);
+            }
+        }
+        Shape::Text {
+            x,
+            y,
+            size,
+            text,
+            color,
+            bold,
+        } => {
+            if let Some(path) = text_path(text, *x as f32, *y as f32, *size as f32) {
+                fill(pixmap, &path, *color, transform);
+                if *bold {
+                    stroke(pixmap, &path, *color, *size as f32 * 0.04, transform);
+                }
+            }
+        }
+    }
+}
+
+fn paint_path(
+    pixmap: &mut Pixmap,
+    path: &Path,
+    fill_color: Option<Color>,
+    outline: Option<(Color, f64)>,
+    transform: Transform,
+) {
+    if let Some(color) = fill_color {
+        fill(pixmap, path, color, transform);
+    }
+    if let Some((color, width)) = outline {
+        stroke(pixmap, path, color, width as f32, transform);
+    }
+}
+
+fn paint(Color(r, g, b): Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn fill(pixmap: &mut Pixmap, path: &Path, color: Color, transform: Transform) {
+    pixmap.fill_path(path, &paint(color), FillRule::Winding, transform, None);
+}
+
+fn stroke(pixmap: &mut Pixmap, path: &Path, color: Color, width: f32, transform: Transform) {
+    let stroke = Stroke {
+        width,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    };
+    pixmap.stroke_path(path, &paint(color), &stroke, transform, None);
+}
+
+/// The outlines of `text` centered at `x`, `y`.
+fn text_path(text: &str, x: f32, y: f32, size: f32) -> Option<Path> {
+    let face: &Face = &FACE;
+    let scale = size / face.units_per_em() as f32;
+    let glyphs: Vec<_> = text.chars().filter_map(|c| face.glyph_index(c)).collect();
+    let width: f32 = glyphs
+        .iter()
+        .filter_map(|glyph| face.glyph_hor_advance(*glyph))
+        .map(|advance| advance as f32 * scale)
+        .sum();
+    let cap_height = face
+        .glyph_index('H')
+        .and_then(|glyph| face.glyph_bounding_box(glyph))
+        .map_or(0.0, |bounds| bounds.y_max as f32 * scale);
+
+    let mut builder = GlyphBuilder {
+        builder: PathBuilder::new(),
+        left: x - width / 2.0,
+        baseline: y + cap_height / 2.0,
+        scale,
+    };
+    for glyph in glyphs {
+        face.outline_glyph(glyph, &mut builder);
+        builder.left += face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * scale;
+    }
+    builder.builder.finish()
+}
+
+/// Converts glyph outlines from font units with y up to drawing units.
+struct GlyphBuilder {
+    builder: PathBuilder,
+    left: f32,
+    baseline: f32,
+    scale: f32,
+}
+
+impl GlyphBuilder {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.left + x * self.scale, self.baseline - y * self.scale)
+    }
+}
+
+impl OutlineBuilder for GlyphBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.builder.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.builder.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        self.builder.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        self.builder.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::variant::Puzzle;
+    use super::super::{draw_sudoku, Options};
+    use super::*;
+
+    #[test]
+    fn test_write_png() {
+        let puzzle: Puzzle = format!("123{}", ".".repeat(78)).parse().unwrap();
+        let drawing = draw_sudoku(&puzzle.sudoku, &Options::default());
+
+        let png = write(&drawing, 2.0).unwrap();
+        assert_eq!(b"\x89PNG", &png[..4]);
+
+        let pixmap = Pixmap::decode_png(&png).unwrap();
+        assert_eq!((drawing.width * 2.0) as u32, pixmap.width());
+        // The middle of the first cell has some ink from the digit 1.
+        let ink = (0..20).any(|offset| {
+            let pixel = pixmap.pixel(80 - 10 + offset, 80).unwrap();
+            pixel.red() < 128
+        });
+        assert!(ink);
+    }
+}
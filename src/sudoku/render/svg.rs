@@ -0,0 +1,183 @@
+//! SVG output.
+
+use super::{Color, Drawing, Shape};
+use std::fmt::Write;
+
+pub fn write(drawing: &Drawing) -> String {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = drawing.width,
+        h = drawing.height
+    )
+    .unwrap();
+
+    for shape in drawing.shapes.iter() {
+        write_shape(&mut svg, shape);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape) {
+    match shape {
+        Shape::Line {
+            points,
+            width,
+            color,
+        } => {
+            let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+            writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points.join(" "),
+                color_string(*color),
+                width
+            )
+            .unwrap();
+        }
+        Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            stroke,
+        } => {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                x,
+                y,
+                width,
+                height,
+                paint(*fill, *stroke)
+            )
+            .unwrap();
+        }
+        Shape::Circle {
+            x,
+            y,
+            radius,
+            fill,
+            stroke,
+        } => {
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                x,
+                y,
+                radius,
+                paint(*fill, *stroke)
+            )
+            .unwrap();
+        }
+        Shape::Text {
+            x,
+            y,
+            size,
+            text,
+            color,
+            bold,
+        } => {
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" font-weight="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x,
+                y,
+                size,
+                if *bold { "bold" } else { "normal" },
+                color_string(*color),
+                escape(text)
+            )
+            .unwrap();
+        }
+    }
+}
+
+fn paint(fill: Option<Color>, stroke: Option<(Color, f64)>) -> String {
+    let fill = fill.map_or(String::from("none"), color_string);
+    match stroke {
+        Some((color, width)) => format!(
+            r#"fill="{}" stroke="{}" stroke-width="{}""#,
+            fill,
+            color_string(color),
+            width
+        ),
+        None => format!(r#"fill="{}""#, fill),
+    }
+}
+
+fn color_string(Color(r, g, b): Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::gattai::Gattai;
+    use super::super::super::variant::Puzzle;
+    use super::super::{draw_gattai, draw_puzzle, Options, CELL_SIZE};
+    use super::*;
+
+    #[test]
+    fn test_write_puzzle() {
+        let puzzle: Puzzle = format!(
+            "12{}
+            thermo r2c1 r2c2 r2c3
+            white r3c1 r3c2
+            even r9c9",
+            ".".repeat(79)
+        )
+        .parse()
+        .unwrap();
+        let mut givens = puzzle.sudoku;
+        givens.cells[1][0] = None;
+
+        let options = Options {
+            title: Some(String::from("Tom & Jerry")),
+            id: Some(String::from("42")),
+            givens: Some(givens),
+            pencil_marks: true,
+            notes: None,
+            highlight: vec![super::super::super::Position { x: 4, y: 4 }],
+        };
+        let svg = write(&draw_puzzle(&puzzle, &options));
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">Tom &amp; Jerry</text>"));
+        assert!(svg.contains(">#42</text>"));
+        // The given 1 is bold and the filled in 2 is not.
+        assert!(svg.contains(r##"font-weight="bold" fill="#000000" text-anchor="middle" dominant-baseline="central">1</text>"##));
+        assert!(svg.contains(r##"font-weight="normal" fill="#1a5fb4" text-anchor="middle" dominant-baseline="central">2</text>"##));
+        assert!(svg.contains(r##"fill="#ffe9a8""##));
+        assert_eq!(9, svg.matches(r#"stroke-width="3""#).count());
+    }
+
+    #[test]
+    fn test_write_gattai() {
+        let mut samurai = Gattai::samurai();
+        samurai
+            .set(super::super::super::Position { x: 20, y: 20 }, Some(7))
+            .unwrap();
+        let drawing = draw_gattai(&samurai, &Options::default());
+        assert!(drawing.width > 21.0 * CELL_SIZE);
+
+        let svg = write(&drawing);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">7</text>"));
+        // The background, 369 cells and 41 boxes. The shared boxes are drawn
+        // once and the gaps between the corner grids are not drawn.
+        assert_eq!(1 + 369 + 41, svg.matches("<rect").count());
+    }
+}
@@ -0,0 +1,245 @@
+//! Terminal output with box drawing characters and colours. `Style::detect`
+//! falls back to plain ASCII without colours when the output is not a
+//! terminal, for example when it is piped to a file.
+
+use super::super::variant::Puzzle;
+use super::super::{solver, validate, Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+use super::Options;
+use std::io::IsTerminal;
+
+const RESET: &str = "\x1b[0m";
+const GIVEN: &str = "\x1b[1m";
+const FILLED: &str = "\x1b[34m";
+const CONFLICT: &str = "\x1b[1;31m";
+const PENCIL_MARK: &str = "\x1b[2m";
+const HIGHLIGHT: &str = "\x1b[43m";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Style {
+    /// Draw the borders with Unicode box drawing characters instead of ASCII.
+    pub unicode: bool,
+    /// Use ANSI colours.
+    pub color: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style {
+        unicode: false,
+        color: false,
+    };
+
+    pub const PRETTY: Style = Style {
+        unicode: true,
+        color: true,
+    };
+
+    /// `PRETTY` if the standard output is a terminal, `PLAIN` otherwise.
+    pub fn detect() -> Style {
+        if std::io::stdout().is_terminal() {
+            Style::PRETTY
+        } else {
+            Style::PLAIN
+        }
+    }
+}
+
+/// The characters of one horizontal border: left end, fill, junction with a
+/// thin border, junction with a thick border and right end.
+struct Border {
+    left: char,
+    fill: char,
+    thin: char,
+    thick: char,
+    right: char,
+}
+
+impl Style {
+    fn border(self, y: usize) -> Border {
+        let (left, fill, thin, thick, right) = match (self.unicode, y) {
+            (false, _) if y.is_multiple_of(BOX_SIZE) => ('+', '=', '+', '+', '+'),
+            (false, _) => ('+', '-', '+', '+', '+'),
+            (true, 0) => ('┏', '━', '┯', '┳', '┓'),
+            (true, SUDOKU_SIZE) => ('┗', '━', '┷', '┻', '┛'),
+            (true, _) if y.is_multiple_of(BOX_SIZE) => ('┣', '━', '┿', '╋', '┫'),
+            (true, _) => ('┠', '─', '┼', '╂', '┨'),
+        };
+        Border {
+            left,
+            fill,
+            thin,
+            thick,
+            right,
+        }
+    }
+
+    fn vertical(self, x: usize) -> char {
+        match (self.unicode, x.is_multiple_of(BOX_SIZE)) {
+            (true, true) => '┃',
+            (true, false) => '│',
+            (false, true) => '|',
+            (false, false) => ':',
+        }
+    }
+
+    fn paint(self, text: String, color: &str) -> String {
+        if self.color && !color.is_empty() {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text
+        }
+    }
+}
+
+pub fn write_sudoku(sudoku: &Sudoku, options: &Options, style: Style) -> String {
+    write_puzzle(
+        &Puzzle {
+            sudoku: *sudoku,
+            constraints: Vec::new(),
+        },
+        options,
+        style,
+    )
+}
+
+/// Draws the grid and lists the variant constraints below it in the puzzle
+/// file format. With pencil marks every cell is a 3x3 grid of candidates.
+pub fn write_puzzle(puzzle: &Puzzle, options: &Options, style: Style) -> String {
+    let cells = &puzzle.sudoku.cells;
+    let conflicts = conflicts(&puzzle.sudoku);
+    let (cell_width, cell_height) = if options.pencil_marks {
+        (2 * BOX_SIZE + 1, BOX_SIZE)
+    } else {
+        (3, 1)
+    };
+
+    let mut output = String::new();
+    for y in 0..=SUDOKU_SIZE {
+        let border = style.border(y);
+        output.push(border.left);
+        for x in 0..SUDOKU_SIZE {
+            if x > 0 {
+                output.push(if x % BOX_SIZE == 0 {
+                    border.thick
+                } else {
+                    border.thin
+                });
+            }
+            output.extend(std::iter::repeat_n(border.fill, cell_width));
+        }
+        output.push(border.right);
+        output.push('\n');
+
+        if y == SUDOKU_SIZE {
+            break;
+        }
+
+        for line in 0..cell_height {
+            for x in 0..SUDOKU_SIZE {
+                output.push(style.vertical(x));
+
+                let position = Position { x, y };
+                let (text, mut color) = match position.value(cells) {
+                    Some(value) => {
+                        let text = if line == cell_height / 2 {
+                            format!("{:^width$}", value, width = cell_width)
+                        } else {
+                            " ".repeat(cell_width)
+                        };
+                        let is_given = match options.givens {
+                            Some(givens) => position.value(&givens.cells).is_some(),
+                            None => puzzle.sudoku.is_given(y, x),
+                        };
+                        let color = if conflicts.contains(&position) {
+                            CONFLICT
+                        } else if is_given {
+                            GIVEN
+                        } else {
+                            FILLED
+                        };
+                        (text, color.to_string())
+                    }
+                    None if options.pencil_marks => {
+                        let candidates = match &options.notes {
+                            Some(notes) => notes.get(Position { x, y }),
+                            None => solver::candidates(puzzle, x, y),
+                        };
+                        let text: String = (1..=BOX_SIZE as u8)
+                            .map(|column| {
+                                let candidate = line as u8 * BOX_SIZE as u8 + column;
+                                if candidates.contains(&candidate) {
+                                    format!(" {}", candidate)
+                                } else {
+                                    String::from("  ")
+                                }
+                            })
+                            .collect();
+                        (text + " ", PENCIL_MARK.to_string())
+                    }
+                    None => (" ".repeat(cell_width), String::new()),
+                };
+
+                if options.highlight.contains(&position) {
+                    color.push_str(HIGHLIGHT);
+                }
+                output.push_str(&style.paint(text, &color));
+            }
+            output.push(style.vertical(SUDOKU_SIZE));
+            output.push('\n');
+        }
+    }
+
+    for constraint in puzzle.constraints.iter() {
+        output.push_str(&constraint.to_string());
+        output.push('\n');
+    }
+    output
+}
+
+/// The cells that have the same digit as another cell in their row, column
+/// or box.
+fn conflicts(sudoku: &Sudoku) -> Vec<Position> {
+    validate::validate(sudoku)
+        .into_iter()
+        .flat_map(|conflict| conflict.cells)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_write_plain() {
+        let puzzle: Puzzle = format!("1.1{}\nodd r9c9", ".".repeat(78)).parse().unwrap();
+        let output = write_puzzle(&puzzle, &Options::default(), Style::PLAIN);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!("+===+===+===+===+===+===+===+===+===+", lines[0]);
+        assert_eq!("| 1 :   : 1 |   :   :   |   :   :   |", lines[1]);
+        assert_eq!("+---+---+---+---+---+---+---+---+---+", lines[2]);
+        assert_eq!("odd r9c9", lines[19]);
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_write_pretty_with_pencil_marks() {
+        let puzzle: Puzzle = format!("1.1{}", ".".repeat(78)).parse().unwrap();
+        let options = Options {
+            pencil_marks: true,
+            ..Options::default()
+        };
+        let output = write_puzzle(&puzzle, &options, Style::PRETTY);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines[0].starts_with("┏━━━━━━━┯━━━━━━━┯━━━━━━━┳"));
+        // The second cell has 2-9 as candidates and the duplicate 1s are conflicts.
+        assert!(lines[1].starts_with(&format!(
+            "┃{}       {}│{}   2 3 {}│",
+            CONFLICT, RESET, PENCIL_MARK, RESET
+        )));
+        assert!(lines[2].contains(&format!("│{} 4 5 6 {}│", PENCIL_MARK, RESET)));
+        assert!(lines[2].contains(&format!("{}   1   {}", CONFLICT, RESET)));
+        assert_eq!(1 + 9 * 4, lines.len());
+    }
+}
@@ -0,0 +1,93 @@
+//! Line-delimited JSON-RPC 2.0 over a reader and a writer, normally stdin and
+//! stdout. Every request is one line and gets one line of response, except
+//! notifications, which have no `id`. The methods are those of `api::call`.
+
+use super::api;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+
+/// Answers the requests until the input ends.
+pub fn serve(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(&line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Answers one request, or returns `None` for a notification.
+pub fn handle(line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+    let id = request.get("id").cloned();
+    let method = match request.get("method").and_then(Value::as_str) {
+        Some(method) if request.get("jsonrpc") == Some(&json!("2.0")) => method,
+        _ => {
+            let message = "expected jsonrpc 2.0 and a method";
+            return Some(error(id.unwrap_or(Value::Null), INVALID_REQUEST, message));
+        }
+    };
+    let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+    let result = api::call(method, &params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error(id, e.code(), &e.to_string()),
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let input = concat!(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "validate", "params": {"squares": []}}"#,
+            "\n\n",
+            r#"{"jsonrpc": "2.0", "method": "validate", "params": {"squares": []}}"#,
+            "\n",
+            r#"{"jsonrpc": "2.0", "id": "a", "method": "solve"}"#,
+            "\n",
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "shuffle"}"#,
+            "\n",
+            "{oops\n",
+        );
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["unique"], false);
+        assert_eq!(responses[1]["id"], "a");
+        assert_eq!(responses[1]["error"]["code"], -32602);
+        assert_eq!(responses[2]["error"]["code"], -32601);
+        assert_eq!(responses[3]["id"], Value::Null);
+        assert_eq!(responses[3]["error"]["code"], PARSE_ERROR);
+    }
+}
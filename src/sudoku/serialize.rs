@@ -0,0 +1,268 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! The grids are strings by default: a `Sudoku` is its 81 cells row by row
+//! with `.` for an empty cell, a `CandidateGrid` its 729 characters, a
+//! `Position` `r1c1` and a `Difficulty` its name. The results of solving and
+//! validating, `solver::Contradiction` and `validate::Conflict`, are objects
+//! with a house such as `{"row": 0}`. The compact `Sudoku` has
+//! only the digits, so every digit is a given when it is read back. Use
+//! `verbose` for a field that must keep the origin of the digits, for
+//! example a solved grid:
+//!
+//! ```text
+//! #[derive(Serialize, Deserialize)]
+//! struct Solved {
+//!     #[serde(with = "sudoku_solver::sudoku::serialize::verbose")]
+//!     sudoku: Sudoku,
+//! }
+//! ```
+
+use super::candidates::CandidateGrid;
+use super::rating::Difficulty;
+use super::variant::Puzzle;
+use super::{Position, Sudoku};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The cells row by row, `.` for an empty cell.
+pub fn to_compact(sudoku: &Sudoku) -> String {
+    sudoku
+        .rows()
+        .flatten()
+        .map(|value| value.map_or('.', |value| (b'0' + value) as char))
+        .collect()
+}
+
+/// Reads the 81 cells of `to_compact`, or the grid of a puzzle file without
+/// constraints. The digits are givens.
+pub fn from_compact(s: &str) -> Result<Sudoku, String> {
+    let puzzle: Puzzle = s.parse()?;
+    if !puzzle.constraints.is_empty() {
+        return Err(String::from("a Sudoku cannot have constraints"));
+    }
+    Ok(puzzle.sudoku)
+}
+
+fn serialize_display<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn deserialize_from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr<Err = String>,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(D::Error::custom)
+}
+
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_compact(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        from_compact(&s).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for CandidateGrid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CandidateGrid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+impl Serialize for Difficulty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// A `Sudoku` as the list of its filled cells with the origin of each digit,
+/// for `#[serde(with = "...")]`.
+pub mod verbose {
+    use super::super::{Origin, Position, Sudoku, SUDOKU_SIZE};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// `row` and `column` start from 0 like in the methods of `Sudoku`.
+    #[derive(Serialize, Deserialize)]
+    struct VerboseCell {
+        row: usize,
+        column: usize,
+        value: u8,
+        origin: Origin,
+    }
+
+    pub fn serialize<S: Serializer>(sudoku: &Sudoku, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut cells = Vec::new();
+        for row in 0..SUDOKU_SIZE {
+            for column in 0..SUDOKU_SIZE {
+                if let (Some(value), Some(origin)) =
+                    (sudoku.get(row, column), sudoku.origin(row, column))
+                {
+                    cells.push(VerboseCell {
+                        row,
+                        column,
+                        value,
+                        origin,
+                    });
+                }
+            }
+        }
+        cells.serialize(serializer)
+    }
+
+    /// Fails if a cell is outside the grid, has an invalid digit or is
+    /// listed twice.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Sudoku, D::Error> {
+        let mut sudoku = Sudoku::empty();
+        for cell in Vec::<VerboseCell>::deserialize(deserializer)? {
+            if cell.row >= SUDOKU_SIZE
+                || cell.column >= SUDOKU_SIZE
+                || !(1..=9).contains(&cell.value)
+            {
+                return Err(D::Error::custom(format!(
+                    "invalid cell row {} column {} value {}",
+                    cell.row, cell.column, cell.value
+                )));
+            }
+            let position = Position {
+                x: cell.column,
+                y: cell.row,
+            };
+            if sudoku.value(position).is_some() {
+                return Err(D::Error::custom(format!(
+                    "cell row {} column {} is listed twice",
+                    cell.row, cell.column
+                )));
+            }
+            sudoku.set_cell(position, Some(cell.value), cell.origin);
+        }
+        Ok(sudoku)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hint::{self, Hint};
+    use super::super::rating::{self, Rating};
+    use super::super::solver::{self, Contradiction};
+    use super::super::validate::House;
+    use super::super::Origin;
+    use super::*;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6\
+                          .6....28....419..5....8..79";
+
+    #[test]
+    fn test_compact() {
+        let sudoku = from_compact(PUZZLE).unwrap();
+        let json = serde_json::to_string(&sudoku).unwrap();
+        assert_eq!(json, format!("\"{}\"", PUZZLE));
+        assert_eq!(serde_json::from_str::<Sudoku>(&json).unwrap(), sudoku);
+        assert!(serde_json::from_str::<Sudoku>("\"123\"").is_err());
+
+        let candidates = CandidateGrid::from_sudoku(&sudoku);
+        let json = serde_json::to_string(&candidates).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CandidateGrid>(&json).unwrap(),
+            candidates
+        );
+    }
+
+    #[test]
+    fn test_verbose() {
+        #[derive(Serialize, Deserialize)]
+        struct Solved {
+            #[serde(with = "verbose")]
+            sudoku: Sudoku,
+        }
+
+        let mut sudoku = Sudoku::empty();
+        sudoku.set(0, 0, 5);
+        sudoku.place(0, 1, 3);
+        let json = serde_json::to_value(Solved { sudoku }).unwrap();
+        assert_eq!(
+            json["sudoku"][1],
+            serde_json::json!({ "row": 0, "column": 1, "value": 3, "origin": "user" })
+        );
+
+        assert!(solver::solve(&mut sudoku));
+        let json = serde_json::to_string(&Solved { sudoku }).unwrap();
+        let solved: Solved = serde_json::from_str(&json).unwrap();
+        assert_eq!(solved.sudoku, sudoku);
+        assert_eq!(solved.sudoku.origin(0, 1), Some(Origin::User));
+        assert_eq!(solved.sudoku.origin(8, 8), Some(Origin::Solver));
+
+        let twice = r#"{"sudoku": [
+            {"row": 0, "column": 0, "value": 1, "origin": "given"},
+            {"row": 0, "column": 0, "value": 2, "origin": "user"}
+        ]}"#;
+        assert!(serde_json::from_str::<Solved>(twice).is_err());
+    }
+
+    #[test]
+    fn test_solve_results() {
+        let mut puzzle: Puzzle = format!("5...5....{}", ".".repeat(72)).parse().unwrap();
+        let contradiction = solver::try_solve(&mut puzzle).unwrap_err();
+        let json = serde_json::to_value(&contradiction).unwrap();
+        assert_eq!(json["conflict"]["house"], serde_json::json!({ "row": 0 }));
+        assert_eq!(json["conflict"]["cells"][1], "r1c5");
+        assert_eq!(
+            serde_json::from_value::<Contradiction>(json).unwrap(),
+            contradiction
+        );
+
+        let json = serde_json::to_string(&Contradiction::NoPlace(House::Box(2), 9)).unwrap();
+        assert_eq!(json, r#"{"no_place":[{"box":2},9]}"#);
+        let json = serde_json::to_string(&Contradiction::Exhausted).unwrap();
+        assert_eq!(json, r#""exhausted""#);
+    }
+
+    #[test]
+    fn test_rating_and_hint() {
+        let puzzle: Puzzle = PUZZLE.parse().unwrap();
+        let rating = rating::rate(&puzzle).unwrap();
+        let json = serde_json::to_value(rating).unwrap();
+        assert!(json["difficulty"].is_string());
+        assert_eq!(serde_json::from_value::<Rating>(json).unwrap(), rating);
+
+        let hint = hint::hint(&puzzle).unwrap();
+        let json = serde_json::to_string(&hint).unwrap();
+        assert!(json.contains("\"position\":\"r"));
+        assert_eq!(serde_json::from_str::<Hint>(&json).unwrap(), hint);
+    }
+}
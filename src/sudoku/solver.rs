@@ -1,4 +1,7 @@
-use super::{Cell, Cells, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+use super::validate::{self, Conflict, House};
+use super::variant::{Constraint, Puzzle};
+use super::{Cell, Cells, Origin, Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+use std::fmt;
 
 lazy_static! {
 
@@ -13,107 +16,355 @@ lazy_static! {
                             .map(|value| value + (index as i8)) // convert to index
                             .map(|value| value as usize); // convert to correct type
 
-            return array_init::from_iter(indices).unwrap();
+            array_init::from_iter(indices).unwrap()
         }
 
         let mut arr = [[([0; BOX_SIZE], [0; BOX_SIZE]); SUDOKU_SIZE]; SUDOKU_SIZE];
-        for x in 0..SUDOKU_SIZE {
-            for y in 0..SUDOKU_SIZE {
-                arr[x][y].0 = get_box_indices(x);
-                arr[x][y].1 = get_box_indices(y);
+        for (x, column) in arr.iter_mut().enumerate() {
+            for (y, indices) in column.iter_mut().enumerate() {
+                indices.0 = get_box_indices(x);
+                indices.1 = get_box_indices(y);
             }
         }
         arr
     };
 }
 
-pub fn solve(sudoku: &mut Sudoku) -> bool {
-    return solve_internal(&mut sudoku.cells, 0);
+/// Why a grid has no solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Contradiction {
+    /// A digit is more than once in a house.
+    Conflict(Conflict),
+    /// An empty cell has no candidate.
+    NoCandidate(Position),
+    /// A digit has no place in a house.
+    NoPlace(House, u8),
+    /// Every digit that was guessed led to a contradiction.
+    Exhausted,
 }
 
-fn solve_internal(cells: &mut Cells, num_iteration: u32) -> bool {
-    let mut all_possible_values: Vec<(usize, usize, Vec<u8>)> = Vec::new();
+impl fmt::Display for Contradiction {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Contradiction::Conflict(conflict) => write!(fmt, "{}", conflict),
+            Contradiction::NoCandidate(position) => write!(fmt, "{} has no candidate", position),
+            Contradiction::NoPlace(house, value) => {
+                write!(fmt, "{} has no place in {}", value, house)
+            }
+            Contradiction::Exhausted => write!(fmt, "every guess leads to a contradiction"),
+        }
+    }
+}
 
-    let mut should_finish = false;
-    for x in 0..SUDOKU_SIZE {
-        for y in 0..SUDOKU_SIZE {
-            if cells[x][y].is_none() {
-                let possible_values = find_value(cells, x, y);
-                should_finish = possible_values.len() == 1;
+/// Fills the empty cells. If there is no solution, the grid is left as it
+/// was.
+pub fn solve(sudoku: &mut Sudoku) -> bool {
+    solve_cells(&mut sudoku.cells, &[]).is_ok()
+}
 
-                all_possible_values.push((x, y, possible_values));
+/// Solves a variant puzzle. The digits must follow the variant constraints
+/// in addition to the normal sudoku rules.
+pub fn solve_puzzle(puzzle: &mut Puzzle) -> bool {
+    try_solve(puzzle).is_ok()
+}
 
-                if should_finish {
-                    break;
-                }
-            }
-        }
+/// Like `solve_puzzle` but tells why there is no solution. The reason is
+/// exact when the contradiction is found before the first guess, otherwise
+/// it is `Contradiction::Exhausted`.
+pub fn try_solve(puzzle: &mut Puzzle) -> Result<(), Contradiction> {
+    solve_cells(&mut puzzle.sudoku.cells, &puzzle.constraints)
+}
 
-        if should_finish {
-            break;
-        }
+/// The digits that can be placed to an empty cell without breaking the rules
+/// with the digits that are already in the grid.
+pub(super) fn candidates(puzzle: &Puzzle, x: usize, y: usize) -> Vec<u8> {
+    find_value(&puzzle.sudoku.cells, &puzzle.constraints, x, y)
+}
+
+/// Counts the solutions of the puzzle but stops at `limit`. A limit of 2 is
+/// enough to tell whether the solution is unique.
+pub fn count_solutions(puzzle: &Puzzle, limit: usize) -> usize {
+    if !validate::validate(&puzzle.sudoku).is_empty() {
+        return 0;
+    }
+    let mut cells = puzzle.sudoku.cells;
+    let mut search = Search::new(&mut cells, &puzzle.constraints);
+    match search.find_singles() {
+        Ok(()) => search.count_solutions(limit),
+        Err(_) => 0,
     }
+}
 
-    if all_possible_values.len() == 0 {
-        return true;
+fn solve_cells(cells: &mut Cells, constraints: &[Constraint]) -> Result<(), Contradiction> {
+    if let Some(conflict) = validate::validate(&Sudoku { cells: *cells })
+        .into_iter()
+        .next()
+    {
+        return Err(Contradiction::Conflict(conflict));
+    }
+    let mut search = Search::new(cells, constraints);
+    let result = search.find_singles().and_then(|_| search.solve());
+    if result.is_err() {
+        search.undo(0);
     }
+    result
+}
 
-    let less_possible_values = if should_finish {
-        all_possible_values.last().unwrap()
-    } else {
-        all_possible_values
-            .iter()
-            .min_by_key(|value| value.2.len())
-            .unwrap()
-    };
+/// A grid being solved with the candidates of its empty cells. The
+/// candidates are kept up to date as the digits are placed and rebuilt when
+/// digits are taken back, so a single costs a look at the cells it affects
+/// instead of the whole grid.
+struct Search<'a> {
+    cells: &'a mut Cells,
+    constraints: &'a [Constraint],
+    /// Bit `d - 1` is set when `d` is a candidate, 0 for the filled cells.
+    masks: [[u16; SUDOKU_SIZE]; SUDOKU_SIZE],
+    /// The positions of the digits placed by the solver, so that the digits
+    /// of a failed guess can be taken back with `undo`.
+    placed: Vec<(usize, usize)>,
+    /// The naked and hidden singles that are not placed yet.
+    singles: Vec<(usize, usize, u8)>,
+}
 
-    if less_possible_values.2.len() == 1 {
-        let x = less_possible_values.0;
-        let y = less_possible_values.1;
-        let value = less_possible_values.2[0];
-        cells[x][y] = Some(Cell {
-            value: value,
-            num_iteration: num_iteration,
-        });
+impl<'a> Search<'a> {
+    fn new(cells: &'a mut Cells, constraints: &'a [Constraint]) -> Search<'a> {
+        let mut search = Search {
+            cells,
+            constraints,
+            masks: [[0; SUDOKU_SIZE]; SUDOKU_SIZE],
+            placed: Vec::new(),
+            singles: Vec::new(),
+        };
+        search.rebuild_masks();
+        search
+    }
 
-        return solve_internal(cells, num_iteration);
+    fn rebuild_masks(&mut self) {
+        for x in 0..SUDOKU_SIZE {
+            for y in 0..SUDOKU_SIZE {
+                self.masks[x][y] = match self.cells[x][y] {
+                    Some(_) => 0,
+                    None => mask(&find_value(self.cells, self.constraints, x, y)),
+                };
+            }
+        }
     }
 
-    let x = less_possible_values.0;
-    let y = less_possible_values.1;
-    let size = less_possible_values.2.len();
-    for (index, possible_value) in less_possible_values.2.iter().enumerate() {
-        let is_last = index == size - 1;
-        let next_iteration = if is_last {
-            num_iteration
-        } else {
-            num_iteration + 1
+    fn solve(&mut self) -> Result<(), Contradiction> {
+        let (x, y, possible_values) = match self.propagate()? {
+            Some(less_possible_values) => less_possible_values,
+            None => return Ok(()),
         };
 
-        cells[x][y] = Some(Cell {
-            value: *possible_value,
-            num_iteration: next_iteration,
-        });
+        for value in possible_values {
+            let guess = self.placed.len();
+            if self.place(x, y, value).and_then(|_| self.solve()).is_ok() {
+                return Ok(());
+            }
+            self.undo(guess);
+        }
 
-        let is_finished = solve_internal(cells, next_iteration);
+        Err(Contradiction::Exhausted)
+    }
 
-        if is_finished {
-            return true;
+    /// The digits placed here are left for the caller to take back.
+    fn count_solutions(&mut self, limit: usize) -> usize {
+        match self.propagate() {
+            Err(_) => 0,
+            Ok(None) => 1,
+            Ok(Some((x, y, possible_values))) => {
+                let mut count = 0;
+                for value in possible_values {
+                    let guess = self.placed.len();
+                    if self.place(x, y, value).is_ok() {
+                        count += self.count_solutions(limit - count);
+                    }
+                    self.undo(guess);
+                    if count >= limit {
+                        break;
+                    }
+                }
+                count
+            }
         }
+    }
 
+    /// Finds every naked and hidden single of the grid. Stops at the first
+    /// empty cell without a candidate or digit without a place in a house.
+    fn find_singles(&mut self) -> Result<(), Contradiction> {
         for x in 0..SUDOKU_SIZE {
             for y in 0..SUDOKU_SIZE {
-                if cells[x][y].is_some() && cells[x][y].unwrap().num_iteration == next_iteration {
-                    cells[x][y] = None;
+                if self.cells[x][y].is_none() {
+                    self.check_cell(Position { x, y })?;
                 }
             }
         }
+        for house in House::all() {
+            self.find_hidden_singles(house, ALL_DIGITS)?;
+        }
+        Ok(())
     }
 
-    return false;
+    /// Places the singles until there are none left. Returns the empty cell
+    /// with the fewest candidates to guess next, or `None` if the grid is
+    /// full.
+    fn propagate(&mut self) -> Result<Option<(usize, usize, Vec<u8>)>, Contradiction> {
+        while let Some((x, y, value)) = self.singles.pop() {
+            if self.masks[x][y] & 1 << (value - 1) == 0 {
+                // A cell can be a naked and a hidden single at the same time.
+                let position = Position { x, y };
+                if position.value(self.cells) == Some(value) {
+                    continue;
+                }
+                return Err(Contradiction::NoCandidate(position));
+            }
+            self.place(x, y, value)?;
+        }
+
+        let mut less_possible_values: Option<(usize, usize, u16)> = None;
+        for x in 0..SUDOKU_SIZE {
+            for y in 0..SUDOKU_SIZE {
+                let count = self.masks[x][y].count_ones();
+                let is_less = count > 0
+                    && less_possible_values.is_none_or(|(_, _, less)| count < less.count_ones());
+                if is_less {
+                    less_possible_values = Some((x, y, self.masks[x][y]));
+                }
+            }
+        }
+        Ok(less_possible_values.map(|(x, y, mask)| (x, y, digits(mask).collect())))
+    }
+
+    /// Places `value` and takes it from the candidates of the cells that see
+    /// it and the cells of the constraints through it. The singles this
+    /// leaves are pushed to `singles`.
+    fn place(&mut self, x: usize, y: usize, value: u8) -> Result<(), Contradiction> {
+        let position = Position { x, y };
+        let bit = 1 << (value - 1);
+        self.cells[x][y] = Some(Cell {
+            value,
+            origin: Origin::Solver,
+        });
+        self.placed.push((x, y));
+
+        // The digits each house lost a place for.
+        let mut lost: Vec<(House, u16)> = Vec::new();
+        lose(&mut lost, position, self.masks[x][y] & !bit);
+        self.masks[x][y] = 0;
+
+        for house in House::containing(position).iter() {
+            for peer in house.cells() {
+                self.take(&mut lost, peer, bit)?;
+            }
+        }
+        for constraint in self.constraints {
+            for peer in constraint.affected(position) {
+                if peer.value(self.cells).is_none() {
+                    let allowed = mask(&find_value(self.cells, self.constraints, peer.x, peer.y));
+                    self.take(&mut lost, peer, !allowed)?;
+                }
+            }
+        }
+
+        for (house, values) in lost {
+            self.find_hidden_singles(house, values)?;
+        }
+        Ok(())
+    }
+
+    /// Takes the digits of `values` from the candidates of `position`.
+    fn take(
+        &mut self,
+        lost: &mut Vec<(House, u16)>,
+        position: Position,
+        values: u16,
+    ) -> Result<(), Contradiction> {
+        let removed = self.masks[position.x][position.y] & values;
+        if removed == 0 {
+            return Ok(());
+        }
+        self.masks[position.x][position.y] &= !removed;
+        lose(lost, position, removed);
+        self.check_cell(position)
+    }
+
+    /// Finds a naked single in an empty cell.
+    fn check_cell(&mut self, position: Position) -> Result<(), Contradiction> {
+        let mask = self.masks[position.x][position.y];
+        match mask.count_ones() {
+            0 => Err(Contradiction::NoCandidate(position)),
+            1 => {
+                let value = digits(mask).next().unwrap();
+                self.singles.push((position.x, position.y, value));
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Finds the digits of `values` that have only one place left in
+    /// `house`.
+    fn find_hidden_singles(&mut self, house: House, values: u16) -> Result<(), Contradiction> {
+        for value in digits(values) {
+            if house
+                .cells()
+                .any(|position| position.value(self.cells) == Some(value))
+            {
+                continue;
+            }
+            let mut places = house
+                .cells()
+                .filter(|position| self.masks[position.x][position.y] & 1 << (value - 1) != 0);
+            match (places.next(), places.next()) {
+                (None, _) => return Err(Contradiction::NoPlace(house, value)),
+                (Some(position), None) => self.singles.push((position.x, position.y, value)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Empties the cells placed after the first `count` ones and forgets the
+    /// singles that were not placed.
+    fn undo(&mut self, count: usize) {
+        for (x, y) in self.placed.drain(count..) {
+            self.cells[x][y] = None;
+        }
+        self.singles.clear();
+        self.rebuild_masks();
+    }
+}
+
+const ALL_DIGITS: u16 = (1 << SUDOKU_SIZE) - 1;
+
+fn mask(values: &[u8]) -> u16 {
+    values.iter().fold(0, |mask, value| mask | 1 << (value - 1))
+}
+
+/// The digits whose bits are set in `mask`, smallest first.
+fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=SUDOKU_SIZE as u8).filter(move |value| mask & 1 << (value - 1) != 0)
 }
 
-fn find_value(cells: &mut Cells, x: usize, y: usize) -> Vec<u8> {
+/// Adds `values` to the digits lost by the houses of `position`.
+fn lose(lost: &mut Vec<(House, u16)>, position: Position, values: u16) {
+    if values == 0 {
+        return;
+    }
+    for house in House::containing(position).iter() {
+        match lost.iter_mut().find(|(other, _)| other == house) {
+            Some((_, lost_values)) => *lost_values |= values,
+            None => lost.push((*house, values)),
+        }
+    }
+}
+
+fn find_value(cells: &Cells, constraints: &[Constraint], x: usize, y: usize) -> Vec<u8> {
     // [Some(1), Some(2), ...];
     let mut possible_values: [Option<u8>; SUDOKU_SIZE] =
         array_init::array_init(|i| Some((i as u8) + 1));
@@ -124,12 +375,12 @@ fn find_value(cells: &mut Cells, x: usize, y: usize) -> Vec<u8> {
         }
     };
 
-    for rolling in 0..SUDOKU_SIZE {
-        let cell_x = cells[x][rolling];
-        remove_value_if_necessary(&cell_x);
+    for cell in cells[x].iter() {
+        remove_value_if_necessary(cell);
+    }
 
-        let cell_y = cells[rolling][y];
-        remove_value_if_necessary(&cell_y);
+    for column in cells.iter() {
+        remove_value_if_necessary(&column[y]);
     }
 
     let indices = BOX_INDICES[x][y];
@@ -140,7 +391,15 @@ fn find_value(cells: &mut Cells, x: usize, y: usize) -> Vec<u8> {
         }
     }
 
-    return possible_values.iter().filter_map(|x| *x).collect();
+    possible_values
+        .iter()
+        .filter_map(|x| *x)
+        .filter(|value| {
+            constraints
+                .iter()
+                .all(|constraint| constraint.allows(cells, constraints, x, y, *value))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -155,14 +414,14 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
                 None,
                 Some(Cell {
                     value: 6,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -171,29 +430,29 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 7,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 8,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
             ],
             [
                 Some(Cell {
                     value: 1,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -204,29 +463,29 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 9,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
             ],
             [
                 None,
                 Some(Cell {
                     value: 7,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 5,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 1,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
             ],
@@ -237,7 +496,7 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 8,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -248,29 +507,29 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 9,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 1,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 7,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
             ],
             [
                 Some(Cell {
                     value: 6,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -281,29 +540,29 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 2,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
             ],
             [
                 None,
                 Some(Cell {
                     value: 8,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 6,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 5,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
             ],
@@ -312,14 +571,14 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 9,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -333,14 +592,14 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 6,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -351,12 +610,12 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 8,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 5,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -372,18 +631,18 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 2,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
             ],
             [
                 Some(Cell {
                     value: 5,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -401,12 +660,12 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -415,11 +674,11 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -427,7 +686,7 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 1,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
             ],
@@ -439,13 +698,13 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 5,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
                 Some(Cell {
                     value: 3,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
             ],
             [
@@ -453,15 +712,15 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 9,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 Some(Cell {
                     value: 7,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 Some(Cell {
                     value: 6,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -471,7 +730,7 @@ mod tests {
             [
                 Some(Cell {
                     value: 1,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 None,
@@ -480,12 +739,12 @@ mod tests {
                 None,
                 Some(Cell {
                     value: 7,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
                 None,
                 Some(Cell {
                     value: 4,
-                    num_iteration: 0,
+                    origin: Origin::Given,
                 }),
             ],
         ],
@@ -498,343 +757,343 @@ mod tests {
                 [
                     Some(Cell {
                         value: 7,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 9,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 1,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 1,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 8,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 4,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 6,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 2,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 3,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 1,
+                        origin: Origin::Solver,
                     }),
                 ],
                 [
                     Some(Cell {
                         value: 3,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 1,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 9,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 5,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 7,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 2,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 4,
-                        num_iteration: 0,
+                        origin: Origin::Given,
                     }),
                     Some(Cell {
                         value: 6,
-                        num_iteration: 2,
+                        origin: Origin::Solver,
                     }),
                     Some(Cell {
                         value: 8,
-                        num_iteration: 3,
+                        origin: Origin::Solver,
                     }),
                 ],
             ],
@@ -847,361 +1106,82 @@ mod tests {
     }
 
     #[test]
-    fn test_may_take_long_time_to_solve() {
-        let expected_sudoku = Sudoku {
-            cells: [
-                [
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 10,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 11,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 2,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 12,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 12,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 7,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 9,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 9,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 1,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 9,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 9,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 6,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 13,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 12,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 13,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 12,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 0,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 14,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 15,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 16,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 14,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 16,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 17,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 17,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 16,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 0,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 6,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 5,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 3,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 4,
-                    }),
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 16,
-                    }),
-                ],
-                [
-                    Some(Cell {
-                        value: 1,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 8,
-                        num_iteration: 8,
-                    }),
-                    Some(Cell {
-                        value: 5,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 3,
-                        num_iteration: 10,
-                    }),
-                    Some(Cell {
-                        value: 9,
-                        num_iteration: 17,
-                    }),
-                    Some(Cell {
-                        value: 2,
-                        num_iteration: 17,
-                    }),
-                    Some(Cell {
-                        value: 7,
-                        num_iteration: 0,
-                    }),
-                    Some(Cell {
-                        value: 6,
-                        num_iteration: 16,
-                    }),
-                    Some(Cell {
-                        value: 4,
-                        num_iteration: 0,
-                    }),
-                ],
-            ],
+    fn test_count_solutions() {
+        let puzzle = Puzzle {
+            sudoku: FAST_SUDOKU,
+            constraints: Vec::new(),
+        };
+        assert_eq!(1, count_solutions(&puzzle, 2));
+
+        let mut sudoku = FAST_SUDOKU;
+        sudoku.cells[2][0] = None;
+        sudoku.cells[6][0] = None;
+        sudoku.cells[1][1] = None;
+        sudoku.cells[0][2] = None;
+        let puzzle = Puzzle {
+            sudoku,
+            constraints: Vec::new(),
         };
+        assert_eq!(2, count_solutions(&puzzle, 2));
+    }
 
+    #[test]
+    fn test_may_take_long_time_to_solve() {
         let mut sudoku = SLOW_SUDOKU;
         time_test!();
-        solve(&mut sudoku);
+        assert!(solve(&mut sudoku));
+        // The puzzle has several solutions, any of them will do.
+        assert!(validate::is_solved(&sudoku), "result {}", sudoku);
+        assert_eq!(sudoku.givens(), SLOW_SUDOKU);
+    }
+
+    #[test]
+    fn test_masks_follow_placed_digits() {
+        let puzzle: Puzzle = format!(
+            "{}\nthermo r1c1 r1c2 r1c3 r1c4\nwhite r5c5 r5c6\nnegative x\nxsum left 2 15",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+        let mut cells = puzzle.sudoku.cells;
+        let mut search = Search::new(&mut cells, &puzzle.constraints);
+        search.find_singles().unwrap();
+        for _ in 0..5 {
+            let (x, y, possible_values) = search.propagate().unwrap().unwrap();
+            search.place(x, y, possible_values[0]).unwrap();
+            let masks = search.masks;
+            search.rebuild_masks();
+            assert_eq!(masks, search.masks);
+        }
+    }
+
+    #[test]
+    fn test_contradiction() {
+        let mut puzzle: Puzzle = format!("5.5{}", ".".repeat(78)).parse().unwrap();
+        match try_solve(&mut puzzle) {
+            Err(Contradiction::Conflict(conflict)) => assert_eq!(conflict.value, 5),
+            result => panic!("unexpected {:?}", result),
+        }
+        assert_eq!(count_solutions(&puzzle, 2), 0);
+
+        let mut puzzle: Puzzle = format!("12345678.\n........9\n{}", ".".repeat(63))
+            .parse()
+            .unwrap();
+        let result = try_solve(&mut puzzle);
+        assert_eq!(
+            result,
+            Err(Contradiction::NoCandidate(Position { x: 8, y: 0 }))
+        );
+        assert_eq!(result.unwrap_err().to_string(), "r1c9 has no candidate");
+        assert_eq!(puzzle.sudoku.empty_cells().count(), 72);
+
+        // 9 is blocked from every empty cell of the first row.
+        let mut puzzle: Puzzle = format!("1234.....\n.....9...\n........9\n{}", ".".repeat(54))
+            .parse()
+            .unwrap();
         assert_eq!(
-            expected_sudoku, sudoku,
-            "expected {}\nresult {}",
-            expected_sudoku, sudoku
+            try_solve(&mut puzzle),
+            Err(Contradiction::NoPlace(House::Row(0), 9))
         );
     }
 }
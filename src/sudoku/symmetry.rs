@@ -0,0 +1,157 @@
+//! Symmetries of the clue pattern, the filled cells of a puzzle regardless
+//! of their digits.
+
+use super::{Position, Sudoku, SUDOKU_SIZE};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+    /// The pattern is the same when the grid is turned upside down.
+    Rotational,
+    /// The pattern is the same when the grid is turned a quarter, which also
+    /// makes it `Rotational`.
+    Rotational90,
+    /// The left half mirrors the right half.
+    Mirror,
+    /// The pattern is mirrored over the diagonal from the top left corner.
+    Diagonal,
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Rotational90 => "rotational90",
+            Symmetry::Mirror => "mirror",
+            Symmetry::Diagonal => "diagonal",
+        };
+        write!(fmt, "{}", name)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Symmetry::None),
+            "rotational" => Ok(Symmetry::Rotational),
+            "rotational90" => Ok(Symmetry::Rotational90),
+            "mirror" => Ok(Symmetry::Mirror),
+            "diagonal" => Ok(Symmetry::Diagonal),
+            _ => Err(format!("unknown symmetry '{}'", s)),
+        }
+    }
+}
+
+impl Symmetry {
+    /// The cells that must all be filled or all be empty together with
+    /// `position`, `position` first.
+    pub fn group(self, position: Position) -> Vec<Position> {
+        let last = SUDOKU_SIZE - 1;
+        let Position { x, y } = position;
+        let mut group = match self {
+            Symmetry::None => vec![position],
+            Symmetry::Rotational => vec![
+                position,
+                Position {
+                    x: last - x,
+                    y: last - y,
+                },
+            ],
+            Symmetry::Rotational90 => vec![
+                position,
+                Position { x: last - y, y: x },
+                Position {
+                    x: last - x,
+                    y: last - y,
+                },
+                Position { x: y, y: last - x },
+            ],
+            Symmetry::Mirror => vec![position, Position { x: last - x, y }],
+            Symmetry::Diagonal => vec![position, Position { x: y, y: x }],
+        };
+        // The centre and the cells on an axis are their own images.
+        let mut i = 1;
+        while i < group.len() {
+            if group[..i].contains(&group[i]) {
+                group.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        group
+    }
+
+    /// `true` if the filled cells of `sudoku` follow the symmetry.
+    pub fn holds(self, sudoku: &Sudoku) -> bool {
+        (0..SUDOKU_SIZE * SUDOKU_SIZE).all(|i| {
+            let position = Position {
+                x: i % SUDOKU_SIZE,
+                y: i / SUDOKU_SIZE,
+            };
+            let is_filled = sudoku.value(position).is_some();
+            self.group(position)
+                .iter()
+                .all(|other| sudoku.value(*other).is_some() == is_filled)
+        })
+    }
+}
+
+/// The symmetry of the clue pattern of `sudoku`. If the pattern has several,
+/// the first of `Rotational90`, `Rotational`, `Mirror` and `Diagonal` that
+/// holds is returned.
+pub fn detect(sudoku: &Sudoku) -> Symmetry {
+    [
+        Symmetry::Rotational90,
+        Symmetry::Rotational,
+        Symmetry::Mirror,
+        Symmetry::Diagonal,
+    ]
+    .iter()
+    .copied()
+    .find(|symmetry| symmetry.holds(sudoku))
+    .unwrap_or(Symmetry::None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group() {
+        let corner = Position { x: 0, y: 0 };
+        assert_eq!(Symmetry::Rotational90.group(corner).len(), 4);
+        assert_eq!(
+            Symmetry::Rotational.group(corner),
+            vec![corner, Position { x: 8, y: 8 }]
+        );
+        assert_eq!(Symmetry::Diagonal.group(corner), vec![corner]);
+        let centre = Position { x: 4, y: 4 };
+        assert_eq!(Symmetry::Rotational90.group(centre), vec![centre]);
+        assert_eq!("Mirror".parse(), Ok(Symmetry::Mirror));
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut sudoku = Sudoku::empty();
+        sudoku.set(0, 1, 1);
+        assert_eq!(detect(&sudoku), Symmetry::None);
+        sudoku.set(1, 0, 2);
+        assert_eq!(detect(&sudoku), Symmetry::Diagonal);
+        sudoku.set(0, 7, 3);
+        sudoku.set(1, 8, 4);
+        assert_eq!(detect(&sudoku), Symmetry::Mirror);
+        sudoku.set(8, 7, 5);
+        sudoku.set(7, 8, 6);
+        sudoku.set(8, 1, 7);
+        sudoku.set(7, 0, 8);
+        assert_eq!(detect(&sudoku), Symmetry::Rotational90);
+        sudoku.clear(1, 0);
+        sudoku.clear(7, 8);
+        assert_eq!(detect(&sudoku), Symmetry::Rotational);
+    }
+}
@@ -0,0 +1,165 @@
+//! Checks of a grid against the rules of the classic Sudoku.
+
+use super::{Position, Sudoku, BOX_SIZE, SUDOKU_SIZE};
+use std::fmt;
+
+/// A row, column or box, numbered from 0. The boxes go row by row from the
+/// top left box.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum House {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+}
+
+impl House {
+    /// The 27 houses: the rows, then the columns, then the boxes.
+    pub fn all() -> impl Iterator<Item = House> {
+        (0..SUDOKU_SIZE)
+            .map(House::Row)
+            .chain((0..SUDOKU_SIZE).map(House::Column))
+            .chain((0..SUDOKU_SIZE).map(House::Box))
+    }
+
+    /// The row, the column and the box of `position`.
+    pub fn containing(position: Position) -> [House; 3] {
+        let b = (position.y / BOX_SIZE) * BOX_SIZE + position.x / BOX_SIZE;
+        [
+            House::Row(position.y),
+            House::Column(position.x),
+            House::Box(b),
+        ]
+    }
+
+    /// The cells of the house, rows and boxes from left to right and columns
+    /// from top to bottom.
+    pub fn cells(self) -> impl Iterator<Item = Position> {
+        (0..SUDOKU_SIZE).map(move |i| match self {
+            House::Row(y) => Position { x: i, y },
+            House::Column(x) => Position { x, y: i },
+            House::Box(b) => Position {
+                x: (b % BOX_SIZE) * BOX_SIZE + i % BOX_SIZE,
+                y: (b / BOX_SIZE) * BOX_SIZE + i / BOX_SIZE,
+            },
+        })
+    }
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            House::Row(y) => write!(fmt, "row {}", y + 1),
+            House::Column(x) => write!(fmt, "column {}", x + 1),
+            House::Box(b) => write!(fmt, "box {}", b + 1),
+        }
+    }
+}
+
+/// A digit that is more than once in a house.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    pub house: House,
+    pub value: u8,
+    /// The cells of the house that have the digit, at least two.
+    pub cells: Vec<Position>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{} is more than once in {}:", self.value, self.house)?;
+        for position in self.cells.iter() {
+            write!(fmt, " {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every duplicate digit of the grid, house by house in the order of
+/// `House::all` and by digit within a house. A cell with a duplicate in both
+/// its row and its box is in two conflicts. Empty cells are not conflicts.
+pub fn validate(sudoku: &Sudoku) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    for house in House::all() {
+        for value in 1..=SUDOKU_SIZE as u8 {
+            let cells: Vec<Position> = house
+                .cells()
+                .filter(|position| sudoku.value(*position) == Some(value))
+                .collect();
+            if cells.len() > 1 {
+                conflicts.push(Conflict {
+                    house,
+                    value,
+                    cells,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// `true` if every cell has a digit.
+pub fn is_complete(sudoku: &Sudoku) -> bool {
+    sudoku.empty_cells().next().is_none()
+}
+
+/// `true` if every cell has a digit and there are no conflicts.
+pub fn is_solved(sudoku: &Sudoku) -> bool {
+    is_complete(sudoku) && validate(sudoku).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solver;
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut sudoku = Sudoku::empty();
+        assert!(validate(&sudoku).is_empty());
+        sudoku.set(0, 0, 5);
+        sudoku.set(0, 4, 5);
+        sudoku.set(1, 1, 5);
+        let conflicts = validate(&sudoku);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].house, House::Row(0));
+        assert_eq!(
+            conflicts[0].cells,
+            vec![Position { x: 0, y: 0 }, Position { x: 4, y: 0 }]
+        );
+        assert_eq!(conflicts[1].house, House::Box(0));
+        assert_eq!(
+            conflicts[1].to_string(),
+            "5 is more than once in box 1: r1c1 r2c2"
+        );
+    }
+
+    #[test]
+    fn test_containing() {
+        let position = Position { x: 4, y: 7 };
+        let houses = House::containing(position);
+        assert_eq!(houses, [House::Row(7), House::Column(4), House::Box(7)]);
+        assert!(houses
+            .iter()
+            .all(|house| house.cells().any(|cell| cell == position)));
+    }
+
+    #[test]
+    fn test_is_solved() {
+        let mut sudoku = Sudoku::empty();
+        assert!(!is_complete(&sudoku));
+        assert!(solver::solve(&mut sudoku));
+        assert!(is_complete(&sudoku));
+        assert!(is_solved(&sudoku));
+
+        let value = sudoku.get(0, 0).unwrap();
+        sudoku.set(0, 1, value);
+        assert!(is_complete(&sudoku));
+        assert!(!is_solved(&sudoku));
+    }
+}
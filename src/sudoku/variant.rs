@@ -0,0 +1,402 @@
+//! Variant sudoku constraints. A variant puzzle is a normal sudoku with extra
+//! rules on top of the row, column and box rules. Every rule is its own type
+//! and is wrapped in `Constraint` so that the solver and the puzzle file format
+//! can handle all of them the same way.
+
+pub mod dot;
+pub mod line;
+pub mod outside;
+pub mod restriction;
+
+use super::{Cells, Position, Sudoku, SUDOKU_SIZE};
+pub use dot::{Dot, DotKind, GreaterThan, NegativeDots};
+pub use line::{Arrow, GermanWhispers, Palindrome, Renban, Thermometer};
+pub use outside::{Direction, LittleKiller, Outside, Sandwich, Side, Skyscraper, XSum};
+pub use restriction::{Allowed, CellRestriction};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    Thermometer(Thermometer),
+    Arrow(Arrow),
+    GermanWhispers(GermanWhispers),
+    Renban(Renban),
+    Palindrome(Palindrome),
+    Dot(Dot),
+    GreaterThan(GreaterThan),
+    NegativeDots(NegativeDots),
+    Sandwich(Sandwich),
+    LittleKiller(LittleKiller),
+    Skyscraper(Skyscraper),
+    XSum(XSum),
+    CellRestriction(CellRestriction),
+}
+
+impl Constraint {
+    /// Returns `false` if placing `value` to `x`, `y` would break the
+    /// constraint with the values that are already in the grid. `constraints`
+    /// are all the constraints of the puzzle, because the negative dots depend
+    /// on the dots.
+    pub(super) fn allows(
+        &self,
+        cells: &Cells,
+        constraints: &[Constraint],
+        x: usize,
+        y: usize,
+        value: u8,
+    ) -> bool {
+        let position = Position { x, y };
+        match self {
+            Constraint::Thermometer(thermometer) => thermometer.allows(cells, position, value),
+            Constraint::Arrow(arrow) => arrow.allows(cells, position, value),
+            Constraint::GermanWhispers(whispers) => whispers.allows(cells, position, value),
+            Constraint::Renban(renban) => renban.allows(cells, position, value),
+            Constraint::Palindrome(palindrome) => palindrome.allows(cells, position, value),
+            Constraint::Dot(dot) => dot.allows(cells, position, value),
+            Constraint::GreaterThan(greater) => greater.allows(cells, position, value),
+            Constraint::NegativeDots(negative) => {
+                negative.allows(cells, constraints, position, value)
+            }
+            Constraint::Sandwich(sandwich) => sandwich.allows(cells, position, value),
+            Constraint::LittleKiller(killer) => killer.allows(cells, position, value),
+            Constraint::Skyscraper(skyscraper) => skyscraper.allows(cells, position, value),
+            Constraint::XSum(x_sum) => x_sum.allows(cells, position, value),
+            Constraint::CellRestriction(restriction) => restriction.allows(cells, position, value),
+        }
+    }
+
+    /// The cells whose candidates this constraint may change when a digit is
+    /// placed to `position`, besides the ones that see it by the normal
+    /// sudoku rules. The solver checks them again after every digit it
+    /// places.
+    pub(super) fn affected(&self, position: Position) -> Vec<Position> {
+        let cells = match self {
+            Constraint::Thermometer(thermometer) => thermometer.cells.clone(),
+            Constraint::Arrow(arrow) => std::iter::once(arrow.circle)
+                .chain(arrow.arrow.iter().copied())
+                .collect(),
+            Constraint::GermanWhispers(whispers) => whispers.cells.clone(),
+            Constraint::Renban(renban) => renban.cells.clone(),
+            Constraint::Palindrome(palindrome) => palindrome.cells.clone(),
+            Constraint::Dot(dot) => vec![dot.cells.0, dot.cells.1],
+            Constraint::GreaterThan(greater) => vec![greater.greater, greater.smaller],
+            Constraint::NegativeDots(_) => return dot::orthogonal_neighbours(position).collect(),
+            Constraint::Sandwich(_) | Constraint::Skyscraper(_) | Constraint::XSum(_) => {
+                self.outside_clue().unwrap().0.cells().to_vec()
+            }
+            Constraint::LittleKiller(killer) => killer.cells(),
+            // The allowed digits of a cell do not depend on the other cells.
+            Constraint::CellRestriction(_) => Vec::new(),
+        };
+        if cells.contains(&position) {
+            cells
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The character drawn on the border between two adjacent cells, `a` being
+    /// the left or the upper one.
+    fn border_marker(&self, a: Position, b: Position) -> Option<char> {
+        let horizontal = a.y == b.y;
+        match self {
+            Constraint::Dot(dot) if dot.cells == (a, b) || dot.cells == (b, a) => {
+                Some(match dot.kind {
+                    DotKind::White => 'o',
+                    DotKind::Black => '*',
+                    DotKind::X => 'X',
+                    DotKind::V => 'V',
+                })
+            }
+            Constraint::GreaterThan(greater) if (greater.greater, greater.smaller) == (a, b) => {
+                Some(if horizontal { '>' } else { '∨' })
+            }
+            Constraint::GreaterThan(greater) if (greater.greater, greater.smaller) == (b, a) => {
+                Some(if horizontal { '<' } else { '∧' })
+            }
+            _ => None,
+        }
+    }
+
+    fn is_drawn_on_border(&self) -> bool {
+        matches!(self, Constraint::Dot(_) | Constraint::GreaterThan(_))
+    }
+
+    /// The character drawn to an empty cell, a square for even, a circle for
+    /// odd and a diamond for the cells that allow only some digits. The
+    /// digits do not fit to the cell so the restriction is also listed below
+    /// the grid.
+    fn cell_marker(&self, position: Position) -> Option<char> {
+        match self {
+            Constraint::CellRestriction(restriction) if restriction.cells.contains(&position) => {
+                match restriction.allowed {
+                    Allowed::Even => Some('□'),
+                    Allowed::Odd => Some('○'),
+                    Allowed::Digits(_) => Some('◇'),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn is_drawn_in_cells(&self) -> bool {
+        matches!(
+            self,
+            Constraint::CellRestriction(CellRestriction {
+                allowed: Allowed::Even | Allowed::Odd,
+                ..
+            })
+        )
+    }
+
+    /// The letter that tells the kind of a clue next to a row or a column.
+    pub(super) fn outside_clue_label(&self) -> Option<char> {
+        match self {
+            Constraint::Sandwich(_) => Some('S'),
+            Constraint::Skyscraper(_) => Some('K'),
+            Constraint::XSum(_) => Some('X'),
+            _ => None,
+        }
+    }
+
+    /// The place and the value of a clue that is written next to a row or a column.
+    pub(super) fn outside_clue(&self) -> Option<(Outside, usize)> {
+        match self {
+            Constraint::Sandwich(sandwich) => Some((sandwich.location, sandwich.sum)),
+            Constraint::Skyscraper(skyscraper) => Some((skyscraper.location, skyscraper.count)),
+            Constraint::XSum(x_sum) => Some((x_sum.location, x_sum.sum)),
+            _ => None,
+        }
+    }
+}
+
+/// A sudoku together with its variant constraints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub sudoku: Sudoku,
+    pub constraints: Vec<Constraint>,
+}
+
+impl Puzzle {
+    fn border_marker(&self, a: Position, b: Position) -> Option<char> {
+        self.constraints
+            .iter()
+            .find_map(|constraint| constraint.border_marker(a, b))
+    }
+
+    /// The index of the constraint whose clue is drawn at `location`. Only one
+    /// clue fits to a place so the first one is drawn.
+    fn drawn_outside_clue(&self, location: Outside) -> Option<usize> {
+        self.constraints.iter().position(|constraint| {
+            constraint
+                .outside_clue()
+                .is_some_and(|(clue_location, _)| clue_location == location)
+        })
+    }
+
+    /// The kind letter and the value of the clue drawn at `side`, `index`.
+    fn outside_clue_text(&self, side: Side, index: usize) -> (String, String) {
+        self.drawn_outside_clue(Outside { side, index })
+            .map(|drawn| &self.constraints[drawn])
+            .and_then(|constraint| {
+                Some((constraint.outside_clue_label()?, constraint.outside_clue()?))
+            })
+            .map_or((String::new(), String::new()), |(label, (_, value))| {
+                (label.to_string(), value.to_string())
+            })
+    }
+
+    fn has_outside_clues(&self, side: Side) -> bool {
+        (0..SUDOKU_SIZE).any(|index| !self.outside_clue_text(side, index).1.is_empty())
+    }
+
+    /// Writes the clues above or below the columns with their kind letters
+    /// on the line further from the grid.
+    fn write_outside_rows(
+        &self,
+        fmt: &mut std::fmt::Formatter<'_>,
+        side: Side,
+        margin: &str,
+    ) -> std::fmt::Result {
+        if !self.has_outside_clues(side) {
+            return Ok(());
+        }
+        let (labels, values): (Vec<String>, Vec<String>) = (0..SUDOKU_SIZE)
+            .map(|x| self.outside_clue_text(side, x))
+            .unzip();
+        let line = |texts: Vec<String>| -> String {
+            let line: String = texts.iter().map(|text| format!("{:>2}", text)).collect();
+            format!("{}{}", margin, line.trim_end())
+        };
+        if side == Side::Top {
+            writeln!(fmt, "{}", line(labels))?;
+            writeln!(fmt, "{}", line(values))
+        } else {
+            writeln!(fmt, "{}", line(values))?;
+            writeln!(fmt, "{}", line(labels))
+        }
+    }
+
+    /// The kinds of the clues next to the rows and columns, for example
+    /// `S sandwich, X x-sum`, or `None` if there are none.
+    fn outside_clue_legend(&self) -> Option<String> {
+        let names = [('S', "sandwich"), ('K', "skyscraper"), ('X', "x-sum")];
+        let legend: Vec<String> = names
+            .iter()
+            .filter(|(label, _)| {
+                self.constraints
+                    .iter()
+                    .enumerate()
+                    .any(|(index, constraint)| {
+                        constraint.outside_clue_label() == Some(*label)
+                            && constraint.outside_clue().is_some_and(|(location, _)| {
+                                self.drawn_outside_clue(location) == Some(index)
+                            })
+                    })
+            })
+            .map(|(label, name)| format!("{} {}", label, name))
+            .collect();
+        if legend.is_empty() {
+            None
+        } else {
+            Some(legend.join(", "))
+        }
+    }
+}
+
+/// Draws the grid like `Sudoku` does with the dot and border markers between
+/// the cells, the parity of the empty cells and the clues of the rows and
+/// columns around the grid. A letter next to a clue tells its kind and a
+/// legend below the grid explains the letters. The rest of the constraints
+/// are listed below the grid in the puzzle file format.
+impl std::fmt::Display for Puzzle {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let margin = if self.has_outside_clues(Side::Left) {
+            "    "
+        } else {
+            ""
+        };
+
+        writeln!(fmt)?;
+        self.write_outside_rows(fmt, Side::Top, margin)?;
+        for y in 0..SUDOKU_SIZE {
+            if !margin.is_empty() {
+                let (label, value) = self.outside_clue_text(Side::Left, y);
+                write!(fmt, "{:>1}{:>2} ", label, value)?;
+            }
+            for x in 0..SUDOKU_SIZE {
+                let border = match x {
+                    0 => None,
+                    _ => self.border_marker(Position { x: x - 1, y }, Position { x, y }),
+                };
+                let position = Position { x, y };
+                let value = match position.value(&self.sudoku.cells) {
+                    Some(value) => (b'0' + value) as char,
+                    None => self
+                        .constraints
+                        .iter()
+                        .find_map(|constraint| constraint.cell_marker(position))
+                        .unwrap_or(' '),
+                };
+                write!(fmt, "{}{}", border.unwrap_or('|'), value)?;
+            }
+            let (label, value) = self.outside_clue_text(Side::Right, y);
+            writeln!(fmt, "|{}{}", value, label)?;
+
+            if y + 1 < SUDOKU_SIZE {
+                let markers: String = (0..SUDOKU_SIZE)
+                    .map(|x| {
+                        let marker =
+                            self.border_marker(Position { x, y }, Position { x, y: y + 1 });
+                        format!(" {}", marker.unwrap_or(' '))
+                    })
+                    .collect();
+                if !markers.trim().is_empty() {
+                    writeln!(fmt, "{}{}", margin, markers.trim_end())?;
+                }
+            }
+        }
+        self.write_outside_rows(fmt, Side::Bottom, margin)?;
+        if let Some(legend) = self.outside_clue_legend() {
+            writeln!(fmt, "{}", legend)?;
+        }
+
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            let is_drawn_outside = constraint
+                .outside_clue()
+                .is_some_and(|(location, _)| self.drawn_outside_clue(location) == Some(index));
+            if !constraint.is_drawn_on_border()
+                && !constraint.is_drawn_in_cells()
+                && !is_drawn_outside
+            {
+                writeln!(fmt, "{}", constraint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_display_border_markers() {
+        let puzzle: Puzzle = format!(
+            "{}
+            white r1c1 r1c2
+            greater r1c1 r2c1
+            renban r9c8 r9c9",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        let lines: Vec<String> = puzzle.to_string().lines().map(String::from).collect();
+        assert_eq!("| o | | | | | | | |", lines[1]);
+        assert_eq!(" ∨", lines[2]);
+        assert_eq!("renban r9c8 r9c9", lines[11]);
+    }
+
+    #[test]
+    fn test_display_cell_restrictions() {
+        let puzzle: Puzzle = format!(
+            "1{}
+            even r1c1 r1c2
+            odd r1c3
+            digits 123 r9c9",
+            ".".repeat(80)
+        )
+        .parse()
+        .unwrap();
+
+        let lines: Vec<String> = puzzle.to_string().lines().map(String::from).collect();
+        assert_eq!("|1|□|○| | | | | | |", lines[1]);
+        assert_eq!("| | | | | | | | |◇|", lines[9]);
+        assert_eq!("digits 123 r9c9", lines[10]);
+    }
+
+    #[test]
+    fn test_display_outside_clues() {
+        let puzzle: Puzzle = format!(
+            "{}
+            sandwich top 2 15
+            skyscraper left 1 3
+            xsum left 1 10
+            xsum right 9 21
+            littlekiller r1c2 dl 3",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        let lines: Vec<String> = puzzle.to_string().lines().map(String::from).collect();
+        assert_eq!("       S", lines[1]);
+        assert_eq!("      15", lines[2]);
+        assert_eq!("K 3 | | | | | | | | | |", lines[3]);
+        assert_eq!("    | | | | | | | | | |21X", lines[11]);
+        assert_eq!("S sandwich, K skyscraper, X x-sum", lines[12]);
+        assert_eq!("xsum left 1 10", lines[13]);
+        assert_eq!("littlekiller r1c2 dl 3", lines[14]);
+    }
+}
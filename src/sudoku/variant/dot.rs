@@ -0,0 +1,205 @@
+//! Constraints between two orthogonally adjacent cells. They are drawn as a
+//! marker on the border between the cells.
+
+use super::super::{Cells, Position, SUDOKU_SIZE};
+use super::Constraint;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DotKind {
+    /// Kropki white dot, the digits are consecutive.
+    White,
+    /// Kropki black dot, one digit is double the other.
+    Black,
+    /// The digits sum to 10.
+    X,
+    /// The digits sum to 5.
+    V,
+}
+
+impl DotKind {
+    pub fn holds(self, a: u8, b: u8) -> bool {
+        match self {
+            DotKind::White => (a as i8 - b as i8).abs() == 1,
+            DotKind::Black => a == 2 * b || b == 2 * a,
+            DotKind::X => a + b == 10,
+            DotKind::V => a + b == 5,
+        }
+    }
+}
+
+/// A marker of `kind` between two adjacent cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dot {
+    pub kind: DotKind,
+    pub cells: (Position, Position),
+}
+
+/// A greater-than sign between two adjacent cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreaterThan {
+    pub greater: Position,
+    pub smaller: Position,
+}
+
+/// The negative constraint for `kind`: the relation must not hold between any
+/// adjacent cells that do not have a `Dot` of `kind` between them in the
+/// constraints of the puzzle. The negative constraint for white dots without
+/// any white dots is the non-consecutive rule.
+///
+/// Greater-than signs have no negative form because any two different digits
+/// are either greater or smaller than each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegativeDots {
+    pub kind: DotKind,
+}
+
+impl Dot {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let other = match other_cell(self.cells, position) {
+            Some(other) => other,
+            None => return true,
+        };
+
+        match other.value(cells) {
+            Some(placed) => self.kind.holds(value, placed),
+            // Adjacent cells always see each other so they cannot be equal.
+            None => {
+                (1..=SUDOKU_SIZE as u8).any(|other| other != value && self.kind.holds(value, other))
+            }
+        }
+    }
+}
+
+impl GreaterThan {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        if position == self.greater {
+            value > self.smaller.value(cells).unwrap_or(0)
+        } else if position == self.smaller {
+            value < self.greater.value(cells).unwrap_or(SUDOKU_SIZE as u8 + 1)
+        } else {
+            true
+        }
+    }
+}
+
+impl NegativeDots {
+    pub(super) fn allows(
+        &self,
+        cells: &Cells,
+        constraints: &[Constraint],
+        position: Position,
+        value: u8,
+    ) -> bool {
+        orthogonal_neighbours(position)
+            .filter(|neighbour| !self.is_marked(constraints, position, *neighbour))
+            .filter_map(|neighbour| neighbour.value(cells))
+            .all(|placed| !self.kind.holds(value, placed))
+    }
+
+    fn is_marked(&self, constraints: &[Constraint], a: Position, b: Position) -> bool {
+        constraints.iter().any(|constraint| match constraint {
+            Constraint::Dot(dot) => {
+                dot.kind == self.kind && (dot.cells == (a, b) || dot.cells == (b, a))
+            }
+            _ => false,
+        })
+    }
+}
+
+/// Returns the other cell of the pair if `position` is one of them.
+fn other_cell(cells: (Position, Position), position: Position) -> Option<Position> {
+    if cells.0 == position {
+        Some(cells.1)
+    } else if cells.1 == position {
+        Some(cells.0)
+    } else {
+        None
+    }
+}
+
+pub(super) fn orthogonal_neighbours(position: Position) -> impl Iterator<Item = Position> {
+    let Position { x, y } = position;
+    let left = x.checked_sub(1).map(|x| Position { x, y });
+    let up = y.checked_sub(1).map(|y| Position { x, y });
+    let right = Some(Position { x: x + 1, y }).filter(|p| p.x < SUDOKU_SIZE);
+    let down = Some(Position { x, y: y + 1 }).filter(|p| p.y < SUDOKU_SIZE);
+    left.into_iter().chain(up).chain(right).chain(down)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::solver::solve_puzzle;
+    use super::super::super::{Cell, Origin};
+    use super::super::Puzzle;
+    use super::*;
+
+    fn value(puzzle: &Puzzle, x: usize, y: usize) -> u8 {
+        Position { x, y }.value(&puzzle.sudoku.cells).unwrap()
+    }
+
+    #[test]
+    fn test_dot_kinds() {
+        assert!(DotKind::White.holds(4, 5));
+        assert!(!DotKind::White.holds(4, 6));
+        assert!(DotKind::Black.holds(3, 6));
+        assert!(DotKind::X.holds(3, 7));
+        assert!(DotKind::V.holds(1, 4));
+    }
+
+    #[test]
+    fn test_non_consecutive() {
+        let mut cells: Cells = [[None; SUDOKU_SIZE]; SUDOKU_SIZE];
+        cells[1][0] = Some(Cell {
+            value: 5,
+            origin: Origin::Given,
+        });
+        let negative = NegativeDots {
+            kind: DotKind::White,
+        };
+        let dots = [Constraint::Dot(Dot {
+            kind: DotKind::White,
+            cells: (Position { x: 1, y: 0 }, Position { x: 2, y: 0 }),
+        })];
+
+        assert!(!negative.allows(&cells, &dots, Position { x: 0, y: 0 }, 4));
+        assert!(!negative.allows(&cells, &dots, Position { x: 1, y: 1 }, 6));
+        assert!(negative.allows(&cells, &dots, Position { x: 0, y: 0 }, 7));
+        assert!(negative.allows(&cells, &dots, Position { x: 2, y: 0 }, 6));
+        // A black dot does not mark the cells for the white negative.
+        let black = [Constraint::Dot(Dot {
+            kind: DotKind::Black,
+            cells: (Position { x: 1, y: 0 }, Position { x: 2, y: 0 }),
+        })];
+        assert!(!negative.allows(&cells, &black, Position { x: 2, y: 0 }, 6));
+    }
+
+    #[test]
+    fn test_solve_dots() {
+        let mut puzzle: Puzzle = format!(
+            "{}
+            negative v
+            v r1c1 r1c2
+            black r2c1 r2c2
+            greater r3c1 r2c1",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        assert!(solve_puzzle(&mut puzzle));
+        for x in 0..SUDOKU_SIZE {
+            for y in 0..SUDOKU_SIZE {
+                for neighbour in orthogonal_neighbours(Position { x, y }) {
+                    let a = value(&puzzle, x, y);
+                    let b = value(&puzzle, neighbour.x, neighbour.y);
+                    let marked = (x, y, neighbour.x, neighbour.y) == (0, 0, 1, 0)
+                        || (x, y, neighbour.x, neighbour.y) == (1, 0, 0, 0);
+                    assert_eq!(marked, DotKind::V.holds(a, b));
+                }
+            }
+        }
+        assert!(DotKind::Black.holds(value(&puzzle, 0, 1), value(&puzzle, 1, 1)));
+        assert!(value(&puzzle, 0, 2) > value(&puzzle, 0, 1));
+    }
+}
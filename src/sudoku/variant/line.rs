@@ -0,0 +1,212 @@
+//! Constraints that are drawn as a line through the grid. The cells of a
+//! line are listed in the order they are drawn.
+
+use super::super::{Cells, Position, SUDOKU_SIZE};
+
+/// Digits strictly increase from the bulb, which is the first cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thermometer {
+    pub cells: Vec<Position>,
+}
+
+/// The digit in the circle equals the sum of the digits along the arrow.
+/// Digits may repeat on the arrow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrow {
+    pub circle: Position,
+    pub arrow: Vec<Position>,
+}
+
+/// Neighbouring digits on the line differ by at least 5.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GermanWhispers {
+    pub cells: Vec<Position>,
+}
+
+/// The line contains a set of consecutive digits in any order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renban {
+    pub cells: Vec<Position>,
+}
+
+/// The line reads the same from both ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palindrome {
+    pub cells: Vec<Position>,
+}
+
+impl Thermometer {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let index = match self.cells.iter().position(|cell| *cell == position) {
+            Some(index) => index,
+            None => return true,
+        };
+
+        // There must be room for the smaller digits below and the bigger ones above.
+        let value = value as usize;
+        if value <= index || value + (self.cells.len() - 1 - index) > SUDOKU_SIZE {
+            return false;
+        }
+
+        self.cells
+            .iter()
+            .enumerate()
+            .all(|(other, cell)| match cell.value(cells) {
+                Some(placed) if other < index => placed as usize + (index - other) <= value,
+                Some(placed) if other > index => placed as usize >= value + (other - index),
+                _ => true,
+            })
+    }
+}
+
+impl Arrow {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        if self.circle != position && !self.arrow.contains(&position) {
+            return true;
+        }
+
+        let mut sum = 0;
+        let mut empty = 0;
+        for cell in self.arrow.iter() {
+            if *cell == position {
+                sum += value as usize;
+            } else {
+                match cell.value(cells) {
+                    Some(placed) => sum += placed as usize,
+                    None => empty += 1,
+                }
+            }
+        }
+
+        let circle = if self.circle == position {
+            Some(value)
+        } else {
+            self.circle.value(cells)
+        };
+
+        match circle {
+            Some(circle) => {
+                let circle = circle as usize;
+                sum + empty <= circle && sum + empty * SUDOKU_SIZE >= circle
+            }
+            None => sum + empty <= SUDOKU_SIZE,
+        }
+    }
+}
+
+impl GermanWhispers {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let index = match self.cells.iter().position(|cell| *cell == position) {
+            Some(index) => index,
+            None => return true,
+        };
+
+        // No digit differs from 5 by at least 5.
+        if value == 5 {
+            return false;
+        }
+
+        neighbours(&self.cells, index)
+            .filter_map(|cell| cell.value(cells))
+            .all(|placed| (placed as i8 - value as i8).abs() >= 5)
+    }
+}
+
+impl Renban {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        if !self.cells.contains(&position) {
+            return true;
+        }
+
+        let mut min = value;
+        let mut max = value;
+        for placed in self.cells.iter().filter_map(|cell| cell.value(cells)) {
+            if placed == value {
+                return false;
+            }
+            min = min.min(placed);
+            max = max.max(placed);
+        }
+
+        ((max - min) as usize) < self.cells.len()
+    }
+}
+
+impl Palindrome {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let index = match self.cells.iter().position(|cell| *cell == position) {
+            Some(index) => index,
+            None => return true,
+        };
+
+        let mirror = self.cells[self.cells.len() - 1 - index];
+        match mirror.value(cells) {
+            Some(placed) if mirror != position => placed == value,
+            _ => true,
+        }
+    }
+}
+
+fn neighbours(line: &[Position], index: usize) -> impl Iterator<Item = &Position> {
+    let before = if index > 0 { line.get(index - 1) } else { None };
+    before.into_iter().chain(line.get(index + 1))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::solver::solve_puzzle;
+    use super::super::Puzzle;
+    use super::*;
+
+    fn value(puzzle: &Puzzle, row: usize, column: usize) -> u8 {
+        Position {
+            x: column - 1,
+            y: row - 1,
+        }
+        .value(&puzzle.sudoku.cells)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_thermometer_leaves_room_for_the_line() {
+        let thermometer = Thermometer {
+            cells: (0..4).map(|x| Position { x, y: 0 }).collect(),
+        };
+        let cells: Cells = [[None; SUDOKU_SIZE]; SUDOKU_SIZE];
+        let allowed: Vec<u8> = (1..=9)
+            .filter(|value| thermometer.allows(&cells, Position { x: 1, y: 0 }, *value))
+            .collect();
+        assert_eq!(vec![2, 3, 4, 5, 6, 7], allowed);
+    }
+
+    #[test]
+    fn test_solve_line_constraints() {
+        let mut puzzle: Puzzle = format!(
+            "{}
+            thermo r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9
+            arrow r2c1 r2c2 r2c3
+            whispers r3c4 r3c5 r3c6
+            renban r4c1 r4c2 r4c3 r4c4
+            palindrome r5c1 r6c2 r7c3",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        assert!(solve_puzzle(&mut puzzle));
+        for column in 1..=9 {
+            assert_eq!(column as u8, value(&puzzle, 1, column));
+        }
+        assert_eq!(
+            value(&puzzle, 2, 1),
+            value(&puzzle, 2, 2) + value(&puzzle, 2, 3)
+        );
+        assert!((value(&puzzle, 3, 4) as i8 - value(&puzzle, 3, 5) as i8).abs() >= 5);
+        assert!((value(&puzzle, 3, 5) as i8 - value(&puzzle, 3, 6) as i8).abs() >= 5);
+        let mut renban: Vec<u8> = (1..=4).map(|column| value(&puzzle, 4, column)).collect();
+        renban.sort();
+        assert!(renban.windows(2).all(|pair| pair[1] == pair[0] + 1));
+        assert_eq!(value(&puzzle, 5, 1), value(&puzzle, 7, 3));
+    }
+}
@@ -0,0 +1,290 @@
+//! Constraints given as clues outside the grid. A clue next to a row or a
+//! column reads the cells starting from its own side of the grid.
+
+use super::super::{Cells, Position, SUDOKU_SIZE};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// A place for a clue next to a row or a column. `index` is the column for
+/// `Top` and `Bottom` and the row for `Left` and `Right`, starting from 0.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outside {
+    pub side: Side,
+    pub index: usize,
+}
+
+impl Outside {
+    /// The cells of the row or column in the order they are seen from the clue.
+    pub fn cells(self) -> [Position; SUDOKU_SIZE] {
+        let index = self.index;
+        array_init::array_init(|i| match self.side {
+            Side::Top => Position { x: index, y: i },
+            Side::Bottom => Position {
+                x: index,
+                y: SUDOKU_SIZE - 1 - i,
+            },
+            Side::Left => Position { x: i, y: index },
+            Side::Right => Position {
+                x: SUDOKU_SIZE - 1 - i,
+                y: index,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    DownRight,
+    DownLeft,
+    UpRight,
+    UpLeft,
+}
+
+/// The sum of the digits between 1 and 9 in the row or column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sandwich {
+    pub location: Outside,
+    pub sum: usize,
+}
+
+/// The sum of the digits on the diagonal that starts from `start` and goes
+/// to `direction`. Digits may repeat on the diagonal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LittleKiller {
+    pub start: Position,
+    pub direction: Direction,
+    pub sum: usize,
+}
+
+/// The number of digits seen from the clue when a bigger digit hides all
+/// the smaller ones behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skyscraper {
+    pub location: Outside,
+    pub count: usize,
+}
+
+/// The sum of the first X digits seen from the clue, where X is the first digit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XSum {
+    pub location: Outside,
+    pub sum: usize,
+}
+
+impl Direction {
+    /// The change of the column and the row in one step.
+    pub fn step(self) -> (isize, isize) {
+        match self {
+            Direction::DownRight => (1, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::UpRight => (1, -1),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+}
+
+impl LittleKiller {
+    /// `true` if `start` is on the edge of the grid that the diagonal comes
+    /// from, so that the clue has a place outside the grid before it.
+    pub fn starts_from_edge(&self) -> bool {
+        let (dx, dy) = self.direction.step();
+        let before = |start: usize, step: isize| start as isize - step;
+        let outside = |i: isize| !(0..SUDOKU_SIZE as isize).contains(&i);
+        outside(before(self.start.x, dx)) || outside(before(self.start.y, dy))
+    }
+
+    pub fn cells(&self) -> Vec<Position> {
+        let (dx, dy) = self.direction.step();
+
+        let mut cells = Vec::new();
+        let mut x = self.start.x as isize;
+        let mut y = self.start.y as isize;
+        while (0..SUDOKU_SIZE as isize).contains(&x) && (0..SUDOKU_SIZE as isize).contains(&y) {
+            cells.push(Position {
+                x: x as usize,
+                y: y as usize,
+            });
+            x += dx;
+            y += dy;
+        }
+        cells
+    }
+
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let line = self.cells();
+        if !line.contains(&position) {
+            return true;
+        }
+        let values: Vec<Option<u8>> = line_values(cells, &line, position, value);
+        sum_fits(&values, self.sum, 1, SUDOKU_SIZE)
+    }
+}
+
+impl Sandwich {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let line = self.location.cells();
+        if !line.contains(&position) {
+            return true;
+        }
+        let values = line_values(cells, &line, position, value);
+
+        let one = values.iter().position(|value| *value == Some(1));
+        let nine = values
+            .iter()
+            .position(|value| *value == Some(SUDOKU_SIZE as u8));
+        match (one, nine) {
+            (Some(one), Some(nine)) => {
+                let between = &values[one.min(nine) + 1..one.max(nine)];
+                sum_fits(between, self.sum, 2, SUDOKU_SIZE - 1)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Skyscraper {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let line = self.location.cells();
+        if !line.contains(&position) {
+            return true;
+        }
+        let values = line_values(cells, &line, position, value);
+
+        // Only the filled cells next to the clue are known to be seen.
+        let mut seen = 0;
+        let mut highest = 0;
+        for (index, value) in values.iter().enumerate() {
+            match value {
+                Some(value) if *value > highest => {
+                    seen += 1;
+                    highest = *value;
+                }
+                Some(_) => {}
+                None => {
+                    let remaining = (SUDOKU_SIZE - index).min(SUDOKU_SIZE - highest as usize);
+                    return seen <= self.count && seen + remaining >= self.count;
+                }
+            }
+        }
+        seen == self.count
+    }
+}
+
+impl XSum {
+    pub(super) fn allows(&self, cells: &Cells, position: Position, value: u8) -> bool {
+        let line = self.location.cells();
+        if !line.contains(&position) {
+            return true;
+        }
+        let values = line_values(cells, &line, position, value);
+
+        match values[0] {
+            Some(first) => sum_fits(&values[..first as usize], self.sum, 1, SUDOKU_SIZE),
+            None => true,
+        }
+    }
+}
+
+/// The values of `line` as if `value` was placed to `position`.
+fn line_values(cells: &Cells, line: &[Position], position: Position, value: u8) -> Vec<Option<u8>> {
+    line.iter()
+        .map(|cell| {
+            if *cell == position {
+                Some(value)
+            } else {
+                cell.value(cells)
+            }
+        })
+        .collect()
+}
+
+/// Returns `true` if the empty cells can still be filled with digits between
+/// `min` and `max` so that the values sum to `sum`.
+fn sum_fits(values: &[Option<u8>], sum: usize, min: usize, max: usize) -> bool {
+    let placed: usize = values
+        .iter()
+        .filter_map(|value| *value)
+        .map(usize::from)
+        .sum();
+    let empty = values.iter().filter(|value| value.is_none()).count();
+    placed + empty * min <= sum && placed + empty * max >= sum
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::solver::solve_puzzle;
+    use super::super::Puzzle;
+    use super::*;
+
+    fn row(puzzle: &Puzzle, y: usize) -> Vec<u8> {
+        (0..SUDOKU_SIZE)
+            .map(|x| Position { x, y }.value(&puzzle.sudoku.cells).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_little_killer_cells() {
+        let killer = LittleKiller {
+            start: Position { x: 6, y: 0 },
+            direction: Direction::DownRight,
+            sum: 0,
+        };
+        assert_eq!(
+            vec![
+                Position { x: 6, y: 0 },
+                Position { x: 7, y: 1 },
+                Position { x: 8, y: 2 }
+            ],
+            killer.cells()
+        );
+    }
+
+    #[test]
+    fn test_solve_outside_clues() {
+        let mut puzzle: Puzzle = format!(
+            "{}
+            sandwich left 1 12
+            skyscraper right 2 3
+            xsum left 3 15
+            littlekiller r1c7 dr 10",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        assert!(solve_puzzle(&mut puzzle));
+
+        let first = row(&puzzle, 0);
+        let one = first.iter().position(|value| *value == 1).unwrap();
+        let nine = first.iter().position(|value| *value == 9).unwrap();
+        let between: u8 = first[one.min(nine) + 1..one.max(nine)].iter().sum();
+        assert_eq!(12, between);
+
+        let mut seen = 0;
+        let mut highest = 0;
+        for value in row(&puzzle, 1).iter().rev() {
+            if *value > highest {
+                seen += 1;
+                highest = *value;
+            }
+        }
+        assert_eq!(3, seen);
+
+        let third = row(&puzzle, 2);
+        let x_sum: u8 = third[..third[0] as usize].iter().sum();
+        assert_eq!(15, x_sum);
+
+        let killer: u8 = [(6, 0), (7, 1), (8, 2)]
+            .iter()
+            .map(|(x, y)| row(&puzzle, *y)[*x])
+            .sum();
+        assert_eq!(10, killer);
+    }
+}
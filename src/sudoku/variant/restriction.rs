@@ -0,0 +1,65 @@
+//! Constraints that limit the digits of single cells.
+
+use super::super::{Cells, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Allowed {
+    Even,
+    Odd,
+    /// Any of the listed digits.
+    Digits(Vec<u8>),
+}
+
+impl Allowed {
+    pub fn contains(&self, value: u8) -> bool {
+        match self {
+            Allowed::Even => value.is_multiple_of(2),
+            Allowed::Odd => !value.is_multiple_of(2),
+            Allowed::Digits(digits) => digits.contains(&value),
+        }
+    }
+}
+
+/// The digits of `cells` must be one of the `allowed` ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellRestriction {
+    pub cells: Vec<Position>,
+    pub allowed: Allowed,
+}
+
+impl CellRestriction {
+    pub(super) fn allows(&self, _cells: &Cells, position: Position, value: u8) -> bool {
+        !self.cells.contains(&position) || self.allowed.contains(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::super::solver::solve_puzzle;
+    use super::super::Puzzle;
+    use super::*;
+
+    #[test]
+    fn test_solve_parity_and_digits() {
+        let mut puzzle: Puzzle = format!(
+            "{}
+            even r1c1 r1c2 r1c3 r1c4
+            odd r2c1 r2c2 r2c3 r2c4 r2c5
+            digits 37 r3c9",
+            ".".repeat(81)
+        )
+        .parse()
+        .unwrap();
+
+        assert!(solve_puzzle(&mut puzzle));
+        let cells = &puzzle.sudoku.cells;
+        for x in 0..4 {
+            assert_eq!(0, Position { x, y: 0 }.value(cells).unwrap() % 2);
+        }
+        for x in 0..5 {
+            assert_eq!(1, Position { x, y: 1 }.value(cells).unwrap() % 2);
+        }
+        assert!([3, 7].contains(&Position { x: 8, y: 2 }.value(cells).unwrap()));
+    }
+}
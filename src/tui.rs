@@ -0,0 +1,325 @@
+//! Interactive terminal game.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, execute, terminal as term};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::{Duration, Instant};
+use sudoku_solver::sudoku::game::{Checkpoint, Game, Move};
+use sudoku_solver::sudoku::render::terminal::{self, Style};
+use sudoku_solver::sudoku::render::Options;
+use sudoku_solver::sudoku::variant::Puzzle;
+use sudoku_solver::sudoku::{hint, solver, Position, Sudoku, SUDOKU_SIZE};
+
+const HELP: &str = "arrows/hjkl move  1-9 digit  0 clear  n notes  u undo  r redo  m mark/back  \
+                    ? hint  c check  s save  q quit";
+
+struct State {
+    game: Game,
+    cursor: Position,
+    note_mode: bool,
+    /// The point of the game to go back to after trying a digit.
+    mark: Option<Checkpoint>,
+    /// The unique solution, `None` if the puzzle has none or several.
+    solution: Option<Sudoku>,
+    elapsed: Duration,
+    started: Instant,
+    message: String,
+    marked: Vec<Position>,
+}
+
+/// A game in progress as stored in a save file.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    game: Game,
+    elapsed_seconds: u64,
+}
+
+impl State {
+    fn new(game: Game) -> State {
+        let mut solution = game.givens().clone();
+        let solution =
+            if solver::count_solutions(&solution, 2) == 1 && solver::solve_puzzle(&mut solution) {
+                Some(solution.sudoku)
+            } else {
+                None
+            };
+        State {
+            game,
+            cursor: Position { x: 0, y: 0 },
+            note_mode: false,
+            mark: None,
+            solution,
+            elapsed: Duration::from_secs(0),
+            started: Instant::now(),
+            message: String::new(),
+            marked: Vec::new(),
+        }
+    }
+
+    fn load(path: &str) -> Result<State, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let saved: SavedGame = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let mut state = State::new(saved.game);
+        state.elapsed = Duration::from_secs(saved.elapsed_seconds);
+        Ok(state)
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        let saved = SavedGame {
+            game: self.game.clone(),
+            elapsed_seconds: self.time().as_secs(),
+        };
+        let json = serde_json::to_string_pretty(&saved).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    fn time(&self) -> Duration {
+        self.elapsed + self.started.elapsed()
+    }
+
+    fn play(&mut self, action: Move) {
+        if let Err(error) = self.game.play(action) {
+            self.message = error;
+        }
+    }
+
+    fn enter(&mut self, digit: u8) {
+        if self.note_mode {
+            self.play(Move::ToggleNote(self.cursor, digit));
+        } else if self.game.sudoku().value(self.cursor) == Some(digit) {
+            self.play(Move::Erase(self.cursor));
+        } else {
+            self.play(Move::Place(self.cursor, digit));
+        }
+    }
+
+    fn clear(&mut self) {
+        if self.game.sudoku().value(self.cursor).is_some() {
+            self.play(Move::Erase(self.cursor));
+        } else {
+            self.play(Move::ClearNotes(self.cursor));
+        }
+    }
+
+    fn undo(&mut self) {
+        if !self.game.undo() {
+            self.message = "Nothing to undo".to_string();
+        }
+    }
+
+    fn redo(&mut self) {
+        if !self.game.redo() {
+            self.message = "Nothing to redo".to_string();
+        }
+    }
+
+    /// Marks the current point, or goes back to the marked point and forgets
+    /// it.
+    fn toggle_mark(&mut self) {
+        match self.mark.take() {
+            Some(mark) => {
+                self.message = match self.game.revert(mark) {
+                    Ok(()) => "Back at the mark".to_string(),
+                    Err(error) => error,
+                };
+            }
+            None => {
+                self.mark = Some(self.game.checkpoint());
+                self.message = "Marked, press m again to come back here".to_string();
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let x = self.cursor.x as isize + dx;
+        let y = self.cursor.y as isize + dy;
+        self.cursor = Position {
+            x: x.clamp(0, SUDOKU_SIZE as isize - 1) as usize,
+            y: y.clamp(0, SUDOKU_SIZE as isize - 1) as usize,
+        };
+    }
+
+    /// The entries of the player that differ from the solution.
+    fn mistakes(&self, solution: &Sudoku) -> Vec<Position> {
+        (0..SUDOKU_SIZE * SUDOKU_SIZE)
+            .map(|i| Position {
+                x: i % SUDOKU_SIZE,
+                y: i / SUDOKU_SIZE,
+            })
+            .filter(|position| {
+                let value = self.game.sudoku().value(*position);
+                value.is_some() && value != solution.value(*position)
+            })
+            .collect()
+    }
+
+    fn hint(&mut self) {
+        if let Some(solution) = self.solution {
+            if let Some(position) = self.mistakes(&solution).first() {
+                self.message = format!("{} is wrong", position);
+                self.marked = vec![*position];
+                return;
+            }
+        }
+        match hint::hint(self.game.puzzle()) {
+            Some(hint) => {
+                self.message = hint.to_string();
+                self.marked = vec![hint.position];
+            }
+            None => self.message = "No hint available".to_string(),
+        }
+    }
+
+    fn check(&mut self) {
+        let solution = match self.solution {
+            Some(solution) => solution,
+            None => {
+                self.message = "The puzzle does not have a unique solution".to_string();
+                return;
+            }
+        };
+        let mistakes = self.mistakes(&solution);
+        self.message = if mistakes.len() == 1 {
+            "1 wrong digit".to_string()
+        } else if !mistakes.is_empty() {
+            format!("{} wrong digits", mistakes.len())
+        } else if *self.game.sudoku() == solution {
+            let seconds = self.time().as_secs();
+            format!("Solved in {}:{:02}!", seconds / 60, seconds % 60)
+        } else {
+            "No mistakes so far".to_string()
+        };
+        self.marked = mistakes;
+    }
+
+    fn draw(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let notes = self.game.notes();
+        let mut highlight = self.marked.clone();
+        highlight.push(self.cursor);
+        let options = Options {
+            givens: Some(self.game.givens().sudoku),
+            pencil_marks: !notes.is_empty(),
+            notes: Some(*notes),
+            highlight,
+            ..Options::default()
+        };
+        let grid = terminal::write_puzzle(self.game.puzzle(), &options, Style::PRETTY);
+        let seconds = self.time().as_secs();
+        execute!(out, cursor::MoveTo(0, 0), term::Clear(term::ClearType::All))?;
+        write!(out, "{}", grid.replace('\n', "\r\n"))?;
+        write!(
+            out,
+            "\r\n{}:{:02}  {}{}\r\n{}\r\n{}\r\n",
+            seconds / 60,
+            seconds % 60,
+            if self.note_mode { "notes" } else { "digits" },
+            if self.mark.is_some() { "  marked" } else { "" },
+            self.message,
+            HELP
+        )?;
+        out.flush()
+    }
+}
+
+/// Plays `puzzle` in the terminal. The game is saved to `save_file`.
+pub fn play(puzzle: Puzzle, save_file: &str) -> Result<(), String> {
+    run(State::new(Game::new(puzzle)), save_file)
+}
+
+/// Continues the game saved in `save_file`.
+pub fn resume(save_file: &str) -> Result<(), String> {
+    run(State::load(save_file)?, save_file)
+}
+
+fn run(mut state: State, save_file: &str) -> Result<(), String> {
+    let mut out = std::io::stdout();
+    term::enable_raw_mode().map_err(|e| e.to_string())?;
+    let result = execute!(out, term::EnterAlternateScreen, cursor::Hide)
+        .and_then(|_| event_loop(&mut state, save_file, &mut out));
+    let _ = execute!(out, cursor::Show, term::LeaveAlternateScreen);
+    let _ = term::disable_raw_mode();
+    result.map_err(|e| e.to_string())
+}
+
+fn event_loop(state: &mut State, save_file: &str, out: &mut impl Write) -> std::io::Result<()> {
+    loop {
+        state.draw(out)?;
+        // Wake up regularly to update the timer.
+        if !event::poll(Duration::from_millis(500))? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        state.message.clear();
+        state.marked.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Left | KeyCode::Char('h') => state.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => state.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => state.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => state.move_cursor(0, 1),
+            KeyCode::Char(c @ '1'..='9') => state.enter(c as u8 - b'0'),
+            KeyCode::Char('0') | KeyCode::Char('.') | KeyCode::Backspace | KeyCode::Delete => {
+                state.clear()
+            }
+            KeyCode::Char('m') => state.toggle_mark(),
+            KeyCode::Char('n') => state.note_mode = !state.note_mode,
+            KeyCode::Char('u') => state.undo(),
+            KeyCode::Char('r') => state.redo(),
+            KeyCode::Char('?') => state.hint(),
+            KeyCode::Char('c') => state.check(),
+            KeyCode::Char('s') => {
+                state.message = match state.save(save_file) {
+                    Ok(()) => format!("Saved to {}", save_file),
+                    Err(error) => format!("Failed to save: {}", error),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_and_save() {
+        let puzzle: Puzzle = "53..7....\n6..195...\n.98....6.\n8...6...3\n4..8.3..1\n\
+                              7...2...6\n.6....28.\n...419..5\n....8..79\n"
+            .parse()
+            .unwrap();
+        let mut state = State::new(Game::new(puzzle));
+        assert!(state.solution.is_some());
+
+        state.enter(4);
+        assert_eq!(state.message, "r1c1 is given");
+        state.cursor = Position { x: 2, y: 0 };
+        state.toggle_mark();
+        state.enter(1);
+        state.note_mode = true;
+        state.cursor = Position { x: 3, y: 0 };
+        state.enter(6);
+        state.check();
+        assert_eq!(state.message, "1 wrong digit");
+        assert_eq!(state.marked, vec![Position { x: 2, y: 0 }]);
+
+        let name = format!("sudoku-test-game-{}.json", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        state.save(path).unwrap();
+        let mut loaded = State::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.game.puzzle(), state.game.puzzle());
+        assert_eq!(loaded.game.notes(), state.game.notes());
+
+        state.toggle_mark();
+        assert_eq!(state.game.sudoku().value(Position { x: 2, y: 0 }), None);
+        loaded.undo();
+        loaded.undo();
+        assert_eq!(loaded.game.puzzle(), state.game.puzzle());
+    }
+}
@@ -0,0 +1,114 @@
+//! JavaScript bindings for the WebAssembly build, made with `wasm-bindgen`.
+//! Errors are thrown as JavaScript strings.
+
+use crate::sudoku::api;
+#[cfg(feature = "generator")]
+use crate::sudoku::generator;
+use crate::sudoku::hint;
+#[cfg(feature = "generator")]
+use crate::sudoku::rating::Difficulty;
+use crate::sudoku::variant::Puzzle;
+use crate::sudoku::{format, solver, Position};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = Puzzle)]
+pub struct WasmPuzzle {
+    puzzle: Puzzle,
+}
+
+#[wasm_bindgen(js_class = Puzzle)]
+impl WasmPuzzle {
+    /// Parses a puzzle in the puzzle file format.
+    #[wasm_bindgen(constructor)]
+    pub fn parse(text: &str) -> Result<WasmPuzzle, JsValue> {
+        let puzzle = text.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        Ok(WasmPuzzle { puzzle })
+    }
+
+    /// The 81 digits in row-major order, 0 for an empty cell.
+    pub fn cells(&self) -> Vec<u8> {
+        (0..81)
+            .map(|i| {
+                let position = Position { x: i % 9, y: i / 9 };
+                self.puzzle.sudoku.value(position).unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// Solves the puzzle in place. Returns `false` if there is no solution.
+    pub fn solve(&mut self) -> bool {
+        solver::solve_puzzle(&mut self.puzzle)
+    }
+
+    #[wasm_bindgen(js_name = countSolutions)]
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        solver::count_solutions(&self.puzzle, limit)
+    }
+
+    /// The next digit to place, or `undefined` if the grid is full or has no
+    /// solution.
+    pub fn hint(&self) -> Option<Hint> {
+        hint::hint(&self.puzzle).map(|hint| Hint {
+            row: hint.position.y,
+            column: hint.position.x,
+            value: hint.value,
+            technique: hint.technique.to_string(),
+            message: hint.to_string(),
+        })
+    }
+
+    /// The puzzle in the puzzle file format.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn write(&self) -> String {
+        format::write(&self.puzzle)
+    }
+}
+
+#[cfg(feature = "generator")]
+#[wasm_bindgen(js_class = Puzzle)]
+impl WasmPuzzle {
+    /// Generates a puzzle of level 1 (easy) to 3 (hard).
+    pub fn generate(level: u8) -> Result<WasmPuzzle, JsValue> {
+        let difficulty = Difficulty::from_level(level)
+            .ok_or_else(|| JsValue::from_str("level must be 1, 2 or 3"))?;
+        Ok(WasmPuzzle {
+            puzzle: Puzzle {
+                sudoku: generator::generate(difficulty),
+                constraints: Vec::new(),
+            },
+        })
+    }
+}
+
+/// A digit to place, `row` and `column` starting from 0.
+#[wasm_bindgen]
+pub struct Hint {
+    pub row: usize,
+    pub column: usize,
+    pub value: u8,
+    technique: String,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl Hint {
+    #[wasm_bindgen(getter)]
+    pub fn technique(&self) -> String {
+        self.technique.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+/// Runs an operation of the JSON API with JSON parameters and returns the
+/// JSON result, like a request to the HTTP server.
+#[wasm_bindgen]
+pub fn call(method: &str, params: &str) -> Result<String, JsValue> {
+    let params = serde_json::from_str(params).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    api::call(method, &params)
+        .map(|result| result.to_string())
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}